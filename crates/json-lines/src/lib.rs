@@ -1,10 +1,45 @@
-use futures_util::{TryStream, TryStreamExt, future::ready};
-use serde::de::DeserializeOwned;
-use std::io::Error;
+use futures_util::{Stream, StreamExt, TryStream, TryStreamExt, future::ready};
+use serde::{Serialize, de::DeserializeOwned};
+use std::io;
 use std::path::Path;
-use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf,
+};
+use tokio::time::Sleep;
 use tokio_stream::wrappers::LinesStream;
 
+/// Interval at which a followed file is checked for new lines
+pub const FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] io::Error),
+
+    #[error("Line {line}: {source}: {text}")]
+    Json {
+        line: usize,
+        text: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Serialization error: {0}")]
+    Serialize(serde_json::Error),
+}
+
+/// Keeps `?` working for callers of version 0.1, where the streams yielded [`io::Error`]
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IO(error) => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
 pub struct Lines<R: AsyncBufRead> {
     reader: LinesStream<R>,
 }
@@ -22,23 +57,80 @@ impl<R: AsyncBufRead> Lines<R> {
     where
         O: DeserializeOwned,
     {
-        self.reader
-            .and_then(|line| ready(serde_json::from_str::<O>(&line).map_err(Error::other)))
+        parse(self.reader)
     }
 }
 
-pub async fn cursor_reader(s: &'static str) -> Result<impl AsyncBufRead, Error> {
+/// Parse every line, numbering lines from 1
+fn parse<O, S>(lines: S) -> impl TryStream<Ok = O, Error = Error>
+where
+    O: DeserializeOwned,
+    S: Stream<Item = io::Result<String>>,
+{
+    lines.enumerate().map(|(index, line)| {
+        let text = line?;
+        serde_json::from_str::<O>(&text).map_err(|source| Error::Json {
+            line: index + 1,
+            text,
+            source,
+        })
+    })
+}
+
+pub async fn cursor_reader(s: &'static str) -> Result<impl AsyncBufRead, io::Error> {
     Ok(s.as_bytes())
 }
 
-pub async fn file_reader<P>(path: P) -> Result<impl AsyncBufRead, Error>
+pub async fn file_reader<P>(path: P) -> Result<impl AsyncBufRead, io::Error>
+where
+    P: AsRef<Path>,
+{
+    tokio::fs::File::open(path).await.map(BufReader::new)
+}
+
+/// Reader that waits for a file to grow instead of reaching its end, like `tail -f`
+pub async fn follow_reader<P>(path: P, interval: Duration) -> Result<impl AsyncBufRead, io::Error>
 where
     P: AsRef<Path>,
 {
-    tokio::fs::File::open(path)
-        .await
-        .map_err(Error::other)
-        .map(BufReader::new)
+    tokio::fs::File::open(path).await.map(|file| {
+        BufReader::new(Follow {
+            inner: file,
+            interval,
+            sleep: None,
+        })
+    })
+}
+
+struct Follow<R> {
+    inner: R,
+    interval: Duration,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Follow<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if let Some(sleep) = self.sleep.as_mut() {
+                if sleep.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.sleep = None;
+            }
+            let filled = buf.filled().len();
+            match Pin::new(&mut self.inner).poll_read(cx, buf) {
+                Poll::Ready(Ok(())) if buf.filled().len() == filled => {
+                    let interval = self.interval;
+                    self.sleep = Some(Box::pin(tokio::time::sleep(interval)));
+                }
+                poll => return poll,
+            }
+        }
+    }
 }
 
 pub fn lines<O, R>(r: R) -> impl TryStream<Ok = O, Error = Error>
@@ -46,14 +138,61 @@ where
     O: DeserializeOwned,
     R: AsyncBufRead,
 {
-    LinesStream::new(r.lines())
-        .and_then(|line| ready(serde_json::from_str::<O>(&line).map_err(Error::other)))
+    parse(LinesStream::new(r.lines()))
+}
+
+/// Like [`lines`], skipping lines that cannot be parsed after passing their error to diagnostic
+///
+/// The stream still ends at the first error reading from r.
+pub fn lines_skip_invalid<O, R>(r: R, mut diagnostic: impl FnMut(&Error)) -> impl Stream<Item = O>
+where
+    O: DeserializeOwned,
+    R: AsyncBufRead,
+{
+    lines::<O, R>(r)
+        .into_stream()
+        .take_while(|result| ready(!matches!(result, Err(Error::IO(_)))))
+        .filter_map(move |result| {
+            ready(match result {
+                Ok(o) => Some(o),
+                Err(error) => {
+                    diagnostic(&error);
+                    None
+                }
+            })
+        })
+}
+
+/// Writes every item as json on a line of its own
+pub struct JsonLinesWriter<W> {
+    writer: W,
+}
+
+impl<W: AsyncWrite + Unpin> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write item and flush, so a reader following the output receives it right away
+    pub async fn write<T: Serialize>(&mut self, item: &T) -> Result<(), Error> {
+        let mut line = serde_json::to_vec(item).map_err(Error::Serialize)?;
+        line.push(b'\n');
+        self.writer.write_all(&line).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use futures_util::TryStreamExt;
+    use futures_util::{StreamExt, TryStreamExt};
     use serde::{Deserialize, Serialize};
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
 
     use super::*;
 
@@ -63,6 +202,13 @@ mod test {
         age: u32,
     }
 
+    fn person(name: &str, age: u32) -> Person {
+        Person {
+            name: name.to_owned(),
+            age,
+        }
+    }
+
     #[tokio::test]
     async fn test_json_lines() {
         let json = r#"{"name": "Paul Min", "age": 30}
@@ -83,4 +229,97 @@ mod test {
             ]
         );
     }
+
+    #[tokio::test]
+    async fn line_number() {
+        let json = "{\"name\": \"Paul Min\", \"age\": 30}\n{\"name\": \"John Doe\"}\n";
+        let error = lines::<Person, _>(json.as_bytes())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        match error {
+            Error::Json { line, text, .. } => {
+                assert_eq!(line, 2);
+                assert_eq!(text, "{\"name\": \"John Doe\"}");
+            }
+            _ => panic!("Expected json error"),
+        }
+        let error = lines::<Person, _>(json.as_bytes())
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(io::Error::from)
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn skip_invalid() {
+        let json =
+            "{\"name\": \"Paul Min\", \"age\": 30}\ninvalid\n{\"name\": \"John Doe\", \"age\": 25}";
+        let mut invalid = vec![];
+        let result = lines_skip_invalid::<Person, _>(json.as_bytes(), |error| {
+            invalid.push(error.to_string())
+        })
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(result, vec![person("Paul Min", 30), person("John Doe", 25)]);
+        assert_eq!(invalid.len(), 1);
+        assert!(invalid[0].starts_with("Line 2: "));
+        assert!(invalid[0].ends_with(": invalid"));
+    }
+
+    #[tokio::test]
+    async fn writer() {
+        let mut writer = JsonLinesWriter::new(vec![]);
+        writer.write(&person("Paul Min", 30)).await.unwrap();
+        writer.write(&person("John Doe", 25)).await.unwrap();
+        let written = writer.into_inner();
+        let result: Vec<Person> = lines(written.as_slice()).try_collect().await.unwrap();
+        assert_eq!(result, vec![person("Paul Min", 30), person("John Doe", 25)]);
+    }
+
+    #[tokio::test]
+    async fn writer_serialize_error() {
+        let mut writer = JsonLinesWriter::new(vec![]);
+        let not_json = std::collections::HashMap::from([((1, 2), 3)]);
+        assert!(matches!(
+            writer.write(&not_json).await,
+            Err(Error::Serialize(_))
+        ));
+        assert!(writer.into_inner().is_empty());
+    }
+
+    #[tokio::test]
+    async fn follow() {
+        let path =
+            std::env::temp_dir().join(format!("json-lines-follow-{}.jsonl", std::process::id()));
+        let file = tokio::fs::File::create(&path).await.unwrap();
+        let mut writer = JsonLinesWriter::new(file);
+        writer.write(&person("Paul Min", 30)).await.unwrap();
+
+        let reader = follow_reader(&path, Duration::from_millis(10))
+            .await
+            .unwrap();
+        let mut followed = lines::<Person, _>(reader).into_stream().boxed();
+        assert_eq!(
+            followed.next().await.unwrap().unwrap(),
+            person("Paul Min", 30)
+        );
+
+        // A line is only parsed when complete
+        let mut file = writer.into_inner();
+        file.write_all(b"{\"name\": \"John Doe\",").await.unwrap();
+        file.flush().await.unwrap();
+        let appended = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            file.write_all(b" \"age\": 25}\n").await.unwrap();
+            file.flush().await.unwrap();
+        });
+        assert_eq!(
+            followed.next().await.unwrap().unwrap(),
+            person("John Doe", 25)
+        );
+        appended.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -1,88 +1,96 @@
-use std::time::Duration;
+use std::sync::LazyLock;
 
-use crate::{
-    args::Args,
-    constant::SS_ASSET,
-    multi_line::MultiLine,
-    status::Status,
-    store::{Lipl, LiplStoreExt},
-};
+use crate::{args::Args, constant::SS_ASSET, span::TextSpan};
 use dioxus::prelude::*;
 use dioxus_native_blitz::use_window;
-use futures_util::TryStreamExt;
-use lipl_display_common::{Command, Message};
-use tokio::time::sleep;
+use futures_util::StreamExt;
+use lipl_display_common::{
+    Command, HandleMessage, LiplScreen, Message, MessageSource, Theme,
+    layout::{Layout, Viewport},
+};
+use lipl_display_headless::Renderer;
+use lipl_message_source::Source;
 // #[cfg(feature = "fullscreen")]
 // use winit::monitor::Fullscreen;
 
-trait ToLines {
-    fn to_lines(&self) -> Vec<String>;
-}
+/// Measurement with the bundled Roboto font, the font of the stylesheet
+static MEASURE: LazyLock<Renderer> =
+    LazyLock::new(|| Renderer::new().expect("Bundled font is valid"));
 
-impl ToLines for String {
-    fn to_lines(&self) -> Vec<String> {
-        self.lines().map(|s| s.trim().to_owned()).collect()
-    }
+fn initial_screen(args: &Args) -> LiplScreen {
+    let mut screen = LiplScreen::new(!args.light, args.font_size as f32);
+    screen.status = args.wait_message.clone();
+    screen
 }
 
 pub fn app() -> Element {
     let args = use_context::<Args>();
-    let store = use_store(|| Lipl::from(args));
-    use_future(move || background_task(store));
-    use_window().set_cursor_visible(false);
+    let source = args.source.clone();
+    let wait_message = args.wait_message.clone();
+    let screen = use_signal(|| initial_screen(&args));
+    use_future(move || background_task(screen, source.clone(), wait_message.clone()));
+    let window = use_window();
+    window.set_cursor_visible(false);
     // #[cfg(feature = "fullscreen")]
     // use_window().set_fullscreen(Some(Fullscreen::Borderless(None)));
 
+    // The window is maximized, its size is read again each time the screen changes
+    let size = window
+        .surface_size()
+        .to_logical::<f32>(window.scale_factor());
+    let screen = screen.read();
+    let layout = Layout::new(&screen, &Viewport::new(size.width, size.height), &*MEASURE);
+    let spans = layout
+        .chords
+        .iter()
+        .chain(&layout.lines)
+        .map(|span| (span, layout.font_size))
+        .chain([(&layout.status, layout.status_font_size)]);
+
     rsx! {
         document::Stylesheet {
             href: SS_ASSET,
         },
         document::Meta { name: "viewport", content: "width=device-width, initial-scale=1.0" },
         body {
-            class: if store.dark().cloned() { "dark" } else { "light" },
-            MultiLine {
-                content: store.part().cloned().to_lines(),
-                font_size: store.font_size().cloned(),
-            }
-            Status {
-                font_size: store.font_size().cloned(),
-                text: store.status().cloned(),
+            class: match screen.theme() {
+                Theme::Dark => "dark",
+                Theme::Light => "light",
+                Theme::HighContrast => "high-contrast",
+            },
+            for (span, font_size) in spans {
+                TextSpan {
+                    x: span.x,
+                    y: span.y,
+                    font_size,
+                    text: span.text.clone(),
+                }
             }
         }
     }
 }
 
-async fn background_task(store: Store<Lipl>) {
-    let r = json_lines::file_reader("/home/paul/Code/dart/lipl_display/lipl-gatt-input.txt")
-        .await
-        .unwrap();
-    let mut s = json_lines::lines::<Message, _>(r);
+async fn background_task(mut screen: Signal<LiplScreen>, source: Source, wait_message: String) {
+    let mut s = match lipl_message_source::open(&source).await {
+        Ok(s) => s,
+        Err(error) => {
+            tracing::error!("Failed to open source {source}: {error}");
+            return;
+        }
+    };
 
-    while let Some(message) = s.try_next().await.unwrap() {
+    while let Some(message) = s.next().await {
         match message {
-            Message::Part(part) => store.part().set(part),
-            Message::Status(status) => store.status().set(status),
-            Message::Command(Command::Dark) => store.dark().set(true),
-            Message::Command(Command::Light) => store.dark().set(false),
-            Message::Command(Command::Increase) => {
-                let font_size = store.font_size().cloned().saturating_add(1);
-                store.font_size().set(font_size);
-            }
-            Message::Command(Command::Decrease) => {
-                let font_size = store.font_size().cloned().saturating_sub(1);
-                store.font_size().set(font_size);
-            }
             Message::Command(Command::Exit) | Message::Command(Command::Poweroff) => {
                 break;
             }
             Message::Command(Command::Wait) => {
-                let wait_message = store.wait_message().cloned();
-                store.status().set(wait_message);
-                store.part().set(String::new());
+                let mut screen = screen.write();
+                screen.handle_message(message);
+                screen.status = wait_message.clone();
             }
+            message => screen.write().handle_message(message),
         }
-
-        let timeout = store.timeout().cloned();
-        sleep(Duration::from_millis(timeout)).await;
     }
+    s.stop().await;
 }
@@ -1,6 +1,7 @@
 use clap::Parser;
+use lipl_message_source::Source;
 
-use crate::constant::{DEFAULT_FONT_SIZE, DEFAULT_STATUS, DEFAULT_TIMEOUT};
+use crate::constant::{DEFAULT_FONT_SIZE, DEFAULT_STATUS};
 
 #[derive(Parser, Clone)]
 #[command(version, about, long_about = None)]
@@ -14,6 +15,7 @@ pub struct Args {
     #[arg(short, long)]
     pub light: bool,
 
-    #[arg(short, long, default_value_t = DEFAULT_TIMEOUT)]
-    pub timeout: u64,
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, daemon, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
 }
@@ -1,6 +1,5 @@
 use dioxus::prelude::*;
 
-pub const DEFAULT_TIMEOUT: u64 = 500;
 pub const SS_ASSET: Asset = asset!("assets/styles.css");
 pub const APP_TITLE: &str = "Lipl Display";
 pub const DEFAULT_STATUS: &str = "Even geduld a.u.b. ...";
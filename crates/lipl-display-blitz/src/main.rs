@@ -10,9 +10,7 @@ use crate::constant::APP_TITLE;
 mod app;
 mod args;
 mod constant;
-mod multi_line;
-mod status;
-mod store;
+mod span;
 
 #[cfg(not(feature = "fullscreen"))]
 fn default_window_attributes() -> Box<WindowAttributes> {
@@ -0,0 +1,24 @@
+use dioxus::prelude::*;
+
+#[derive(Props, PartialEq, Clone)]
+pub struct TextSpanProps {
+    x: f32,
+    y: f32,
+    font_size: f32,
+    text: String,
+}
+
+/// Text at the position given by the layout
+#[component]
+pub fn TextSpan(props: TextSpanProps) -> Element {
+    rsx! {
+        span {
+            class: "span",
+            style: format!(
+                "left: {}px; top: {}px; font-size: {}px;",
+                props.x, props.y, props.font_size
+            ),
+            {props.text}
+        }
+    }
+}
@@ -0,0 +1,447 @@
+//! Which bluetooth devices may write to the display service
+//!
+//! Configured in the access table of [`CONFIG_FILE`]:
+//!
+//! ```toml
+//! [access]
+//! # Devices that may always write
+//! allow = ["AA:BB:CC:DD:EE:FF"]
+//! # The first other device to write claims the display
+//! claim = true
+//! # Commands have to be signed with this secret, see crate::signed
+//! secret = "a long random string shared with the phones"
+//! # Text, status and playlist have to be signed too
+//! sign_all = false
+//! # Messages each device may send each second, and at once
+//! rate = 10.0
+//! burst = 20
+//! ```
+//!
+//! Without allow list and claim every device may write, without secret nothing has to be signed.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_PLAYLIST_UUID, CHARACTERISTIC_STATUS_UUID,
+    CHARACTERISTIC_TEXT_UUID, Error, Message, Result,
+    signed::{COUNTER_FILE, Counter, Envelope},
+    validate::{DEFAULT_BURST, DEFAULT_RATE, RateLimit, validate},
+};
+
+/// Config file shared by the lipl services
+pub const CONFIG_FILE: &str = "/etc/lipl.toml";
+/// Name in the rate limit for messages from local interfaces
+const LOCAL: &str = "local";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct AccessConfig {
+    /// Addresses of devices that may write, as `AA:BB:CC:DD:EE:FF`
+    pub allow: Vec<String>,
+    /// Whether the first device not in the allow list to write claims the display
+    pub claim: bool,
+    /// Secret commands are signed with
+    pub secret: Option<String>,
+    /// Whether every write has to be signed, not only commands
+    pub sign_all: bool,
+    /// File with the highest counter accepted, defaults to [`COUNTER_FILE`]
+    pub counter_file: Option<PathBuf>,
+    /// Messages each device may send each second
+    pub rate: f64,
+    /// Messages each device may send at once
+    pub burst: u32,
+}
+
+impl Default for AccessConfig {
+    fn default() -> Self {
+        Self {
+            allow: vec![],
+            claim: false,
+            secret: None,
+            sign_all: false,
+            counter_file: None,
+            rate: DEFAULT_RATE,
+            burst: DEFAULT_BURST,
+        }
+    }
+}
+
+/// Other tables of the config file are ignored
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    access: AccessConfig,
+}
+
+impl AccessConfig {
+    /// Read the access table from the config file at path, a missing file gives the default config
+    pub fn load(path: &Path) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(s) => s.parse(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+impl std::str::FromStr for AccessConfig {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        toml::from_str::<ConfigFile>(s)
+            .map(|config| config.access)
+            .map_err(|error| Error::Config(error.to_string()))
+    }
+}
+
+/// Access config with the claim made since starting
+#[derive(Debug)]
+pub struct Access {
+    allow: Vec<String>,
+    claim: bool,
+    claimed: Mutex<Option<String>>,
+    secret: Option<String>,
+    sign_all: bool,
+    counter: Mutex<Counter>,
+    rate_limit: RateLimit,
+    rejections: Mutex<HashMap<Discriminant<Error>, usize>>,
+}
+
+impl Default for Access {
+    fn default() -> Self {
+        Self::new(AccessConfig::default())
+    }
+}
+
+impl Access {
+    pub fn new(config: AccessConfig) -> Self {
+        Self {
+            allow: config.allow.iter().map(|a| a.to_uppercase()).collect(),
+            claim: config.claim,
+            claimed: Mutex::new(None),
+            secret: config.secret,
+            sign_all: config.sign_all,
+            counter: Mutex::new(Counter::new(Some(
+                config.counter_file.unwrap_or(COUNTER_FILE.into()),
+            ))),
+            rate_limit: RateLimit::new(config.rate, config.burst),
+            rejections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Access from the config in [`CONFIG_FILE`]
+    pub fn load() -> Result<Self> {
+        AccessConfig::load(Path::new(CONFIG_FILE)).map(Self::new)
+    }
+
+    /// Whether the device with address may write, claiming the display if not claimed yet
+    ///
+    /// Writes without device address are only authorized if every device may write.
+    pub fn authorize(&self, address: Option<&str>) -> bool {
+        if self.allow.is_empty() && !self.claim {
+            return true;
+        }
+        let Some(address) = address.map(str::to_uppercase) else {
+            return false;
+        };
+        if self.allow.contains(&address) {
+            return true;
+        }
+        if !self.claim {
+            return false;
+        }
+        let mut claimed = self
+            .claimed
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        claimed.get_or_insert(address.clone()) == &address
+    }
+
+    /// Address of the device that claimed the display
+    pub fn claimed(&self) -> Option<String> {
+        self.claimed
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .clone()
+    }
+
+    /// Let the next device to write claim the display
+    pub fn reset_claim(&self) {
+        self.claimed
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .take();
+    }
+
+    /// Value written to the characteristic with uuid, taken from its envelope if it has to be signed
+    ///
+    /// Unsigned writes, invalid signatures and replayed counters are rejected.
+    pub fn verify<'a>(&self, uuid: Uuid, value: &'a str) -> Result<Cow<'a, str>> {
+        let Some(secret) = self.secret.as_deref().filter(|_| self.must_sign(uuid)) else {
+            return Ok(Cow::Borrowed(value));
+        };
+        let envelope = serde_json::from_str::<Envelope>(value).map_err(|_| Error::Unsigned)?;
+        envelope.verify(secret.as_bytes(), uuid)?;
+        self.counter
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .advance(envelope.counter)?;
+        Ok(Cow::Owned(envelope.value))
+    }
+
+    /// Message from a complete value written by device to the characteristic with uuid
+    ///
+    /// The device has to stay within the rate limit, the value has to be signed if required,
+    /// and the message is validated with [`validate`].
+    pub fn accept(&self, address: Option<&str>, uuid: Uuid, value: &str) -> Result<Message> {
+        self.rate_limit.check(address.unwrap_or("unknown device"))?;
+        let value = self.verify(uuid, value)?;
+        validate(Message::try_from((value.as_ref(), uuid))?)
+    }
+
+    /// Message from a local interface, as the session bus or http, instead of written by a device
+    ///
+    /// Exit and poweroff are not allowed, nor are messages that have to be signed.
+    /// Messages share a rate limit and are validated with [`validate`].
+    pub fn accept_local(&self, message: Message) -> Result<Message> {
+        if message.is_stop() {
+            return Err(Error::NotAllowed(message.to_string()));
+        }
+        let uuid = match &message {
+            Message::Part(_) => CHARACTERISTIC_TEXT_UUID,
+            Message::Status(_) => CHARACTERISTIC_STATUS_UUID,
+            Message::Command(_) => CHARACTERISTIC_COMMAND_UUID,
+            Message::Playlist(_) => CHARACTERISTIC_PLAYLIST_UUID,
+        };
+        if self.secret.is_some() && self.must_sign(uuid) {
+            return Err(Error::Unsigned);
+        }
+        self.rate_limit.check(LOCAL)?;
+        validate(message)
+    }
+
+    fn must_sign(&self, uuid: Uuid) -> bool {
+        uuid == CHARACTERISTIC_COMMAND_UUID || self.sign_all
+    }
+
+    /// Count a rejected write, returns how often writes were rejected with this kind of error
+    pub fn count_rejection(&self, error: &Error) -> usize {
+        let mut rejections = self
+            .rejections
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        let count = rejections.entry(std::mem::discriminant(error)).or_default();
+        *count += 1;
+        *count
+    }
+}
+
+/// Address of a device from its bluez object path, as `/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF`
+///
+/// # Example
+///
+/// ```
+/// use lipl_display_common::access::device_address;
+/// assert_eq!(
+///     device_address("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF").as_deref(),
+///     Some("AA:BB:CC:DD:EE:FF")
+/// );
+/// ```
+pub fn device_address(object_path: &str) -> Option<String> {
+    object_path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.strip_prefix("dev_"))
+        .map(|address| address.replace('_', ":"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Access, AccessConfig};
+    use crate::signed::Envelope;
+    use crate::{
+        CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_PLAYLIST_UUID, CHARACTERISTIC_TEXT_UUID,
+        Command, Error, Message, Playlist,
+    };
+
+    const PHONE: &str = "AA:BB:CC:DD:EE:FF";
+    const TABLET: &str = "11:22:33:44:55:66";
+
+    #[test]
+    fn config() {
+        let config =
+            "log_level = \"info\"\n[access]\nallow = [\"aa:bb:cc:dd:ee:ff\"]\nclaim = true"
+                .parse::<AccessConfig>()
+                .unwrap();
+        assert_eq!(config.allow, vec!["aa:bb:cc:dd:ee:ff"]);
+        assert!(config.claim);
+        assert_eq!(
+            "log_level = \"info\"".parse::<AccessConfig>().unwrap(),
+            AccessConfig::default()
+        );
+        assert!("[access]\nallow = \"all\"".parse::<AccessConfig>().is_err());
+        assert_eq!(
+            AccessConfig::load(std::path::Path::new("/nonexistent/access.toml")).unwrap(),
+            AccessConfig::default()
+        );
+    }
+
+    #[test]
+    fn unrestricted() {
+        let access = Access::default();
+        assert!(access.authorize(Some(PHONE)));
+        assert!(access.authorize(None));
+    }
+
+    #[test]
+    fn allow_list() {
+        let access = Access::new(AccessConfig {
+            allow: vec![PHONE.to_lowercase()],
+            ..Default::default()
+        });
+        assert!(access.authorize(Some(PHONE)));
+        assert!(!access.authorize(Some(TABLET)));
+        assert!(!access.authorize(None));
+    }
+
+    #[test]
+    fn claim() {
+        let access = Access::new(AccessConfig {
+            claim: true,
+            ..Default::default()
+        });
+        assert!(access.authorize(Some(TABLET)));
+        assert!(!access.authorize(Some(PHONE)));
+        assert!(access.authorize(Some(TABLET)));
+        assert_eq!(access.claimed().as_deref(), Some(TABLET));
+
+        access.reset_claim();
+        assert!(access.authorize(Some(PHONE)));
+        assert!(!access.authorize(Some(TABLET)));
+    }
+
+    #[test]
+    fn signed() {
+        let counter_file =
+            std::env::temp_dir().join(format!("lipl-access-counter-{}", std::process::id()));
+        let access = Access::new(AccessConfig {
+            secret: Some("secret".into()),
+            counter_file: Some(counter_file.clone()),
+            ..Default::default()
+        });
+        let command = |counter| {
+            serde_json::to_string(&Envelope::sign(
+                b"secret",
+                CHARACTERISTIC_COMMAND_UUID,
+                counter,
+                "o",
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            access.verify(CHARACTERISTIC_TEXT_UUID, "Hallo").unwrap(),
+            "Hallo"
+        );
+        assert!(matches!(
+            access.verify(CHARACTERISTIC_COMMAND_UUID, "o"),
+            Err(Error::Unsigned)
+        ));
+        assert_eq!(
+            access
+                .verify(CHARACTERISTIC_COMMAND_UUID, &command(1))
+                .unwrap(),
+            "o"
+        );
+        assert!(matches!(
+            access.verify(CHARACTERISTIC_COMMAND_UUID, &command(1)),
+            Err(Error::Replayed(1))
+        ));
+        let forged = command(2).replace("\"o\"", "\"e\"");
+        assert!(matches!(
+            access.verify(CHARACTERISTIC_COMMAND_UUID, &forged),
+            Err(Error::Signature)
+        ));
+        std::fs::remove_file(&counter_file).unwrap();
+    }
+
+    #[test]
+    fn accept() {
+        let access = Access::new(AccessConfig {
+            burst: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            access
+                .accept(Some(PHONE), CHARACTERISTIC_TEXT_UUID, "Amazing\u{0} grace")
+                .unwrap(),
+            Message::Part("Amazing grace".into())
+        );
+        access
+            .accept(Some(PHONE), CHARACTERISTIC_COMMAND_UUID, "d")
+            .unwrap();
+        let error = access
+            .accept(Some(PHONE), CHARACTERISTIC_COMMAND_UUID, "d")
+            .unwrap_err();
+        assert!(matches!(error, Error::RateLimited));
+        assert_eq!(access.count_rejection(&error), 1);
+        assert_eq!(access.count_rejection(&Error::RateLimited), 2);
+        assert_eq!(access.count_rejection(&Error::Unsigned), 1);
+        access
+            .accept(Some(TABLET), CHARACTERISTIC_COMMAND_UUID, "d")
+            .unwrap();
+        // Escaped control characters are removed after parsing
+        assert_eq!(
+            access
+                .accept(
+                    Some(TABLET),
+                    CHARACTERISTIC_PLAYLIST_UUID,
+                    r#"{"title":"Psalm\u0007 23","parts":["De Heer\u001b is mijn herder"]}"#
+                )
+                .unwrap(),
+            Message::Playlist(Playlist {
+                title: Some("Psalm 23".to_owned()),
+                parts: vec!["De Heer is mijn herder".to_owned()],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn accept_local() {
+        let access = Access::default();
+        assert_eq!(
+            access
+                .accept_local(Message::Status("1/3\u{7}".to_owned()))
+                .unwrap(),
+            Message::Status("1/3".to_owned())
+        );
+        assert_eq!(
+            access
+                .accept_local(Message::Command(Command::Dark))
+                .unwrap(),
+            Message::Command(Command::Dark)
+        );
+        for command in [Command::Exit, Command::Poweroff] {
+            assert!(matches!(
+                access.accept_local(Message::Command(command)),
+                Err(Error::NotAllowed(_))
+            ));
+        }
+
+        let access = Access::new(AccessConfig {
+            secret: Some("secret".into()),
+            ..Default::default()
+        });
+        assert!(matches!(
+            access.accept_local(Message::Command(Command::Dark)),
+            Err(Error::Unsigned)
+        ));
+        assert!(access.accept_local(Message::Part("Hallo".into())).is_ok());
+    }
+}
@@ -0,0 +1,297 @@
+//! ChordPro chords embedded in the lyrics of a part
+//!
+//! A chord is written between square brackets just before the syllable it belongs to,
+//! as in `[G]Amazing [C]grace`. Parsing removes the chords from the lyrics and anchors
+//! every chord to the character offset of that syllable.
+//!
+//! # Example
+//!
+//! ```
+//! use lipl_display_common::chordpro::{Chord, parse};
+//! let lines = parse("[G]Amazing [C]grace");
+//! assert_eq!(lines[0].lyrics, "Amazing grace");
+//! assert_eq!(lines[0].chords[1], Chord { offset: 8, name: "C".to_owned() });
+//! assert_eq!(lines[0].chord_row(), "G       C");
+//! ```
+//!
+//! Parsing is opt-in, a part is plain text unless it is sent as
+//! `{"text":"[G]Amazing [C]grace","chords":true}`, so brackets as in `Refrein [2x]` stay in the lyrics.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Chord anchored to a character offset in the lyrics of a line
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Chord {
+    pub offset: usize,
+    pub name: String,
+}
+
+/// Line of lyrics with the chords to show above it
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChordLine {
+    pub lyrics: String,
+    pub chords: Vec<Chord>,
+}
+
+impl ChordLine {
+    /// Chords positioned above the lyrics for a monospaced font
+    ///
+    /// A chord that would overlap the previous one is moved to the right.
+    pub fn chord_row(&self) -> String {
+        let mut row = String::new();
+        let mut width = 0;
+        for chord in &self.chords {
+            let padding = if width == 0 {
+                chord.offset
+            } else {
+                chord.offset.saturating_sub(width).max(1)
+            };
+            row.extend(std::iter::repeat_n(' ', padding));
+            row.push_str(&chord.name);
+            width += padding + chord.name.chars().count();
+        }
+        row
+    }
+
+    fn to_chordpro(&self) -> String {
+        let mut chords = self.chords.iter().peekable();
+        let mut result = String::new();
+        for (offset, c) in self.lyrics.chars().enumerate() {
+            while let Some(chord) = chords.next_if(|chord| chord.offset <= offset) {
+                result.push_str(&format!("[{}]", chord.name));
+            }
+            result.push(c);
+        }
+        for chord in chords {
+            result.push_str(&format!("[{}]", chord.name));
+        }
+        result
+    }
+}
+
+/// Split text in lines, removing the chords from the lyrics
+///
+/// An opening bracket without a closing bracket on the same line is part of the lyrics.
+pub fn parse(text: &str) -> Vec<ChordLine> {
+    text.lines().map(parse_line).collect()
+}
+
+fn parse_line(line: &str) -> ChordLine {
+    let mut result = ChordLine::default();
+    let mut offset = 0;
+    let mut rest = line;
+    while let Some(start) = rest.find('[') {
+        let Some(length) = rest[start..].find(']') else {
+            break;
+        };
+        let lyrics = &rest[..start];
+        result.lyrics.push_str(lyrics);
+        offset += lyrics.chars().count();
+        result.chords.push(Chord {
+            offset,
+            name: rest[start + 1..start + length].trim().to_owned(),
+        });
+        rest = &rest[start + length + 1..];
+    }
+    result.lyrics.push_str(rest);
+    result
+}
+
+/// Text of a part, with the chords if it was parsed as ChordPro
+///
+/// A part without chords is serialized as a plain string,
+/// a part with chords as its ChordPro text with `"chords":true`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Part {
+    /// Lyrics without chords
+    pub text: String,
+    pub chords: Option<Vec<ChordLine>>,
+}
+
+impl Part {
+    /// Part with the chords of ChordPro text
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::Part;
+    /// assert_eq!(Part::chordpro("[G]Amazing grace").text, "Amazing grace");
+    /// assert_eq!(Part::from("[G]Amazing grace").text, "[G]Amazing grace");
+    /// ```
+    pub fn chordpro(text: &str) -> Self {
+        let lines = parse(text);
+        if lines.iter().all(|line| line.chords.is_empty()) {
+            return Self::from(text);
+        }
+        Self {
+            text: lines
+                .iter()
+                .map(|line| line.lyrics.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            chords: Some(lines),
+        }
+    }
+
+    /// Text with the chords between square brackets
+    pub fn to_chordpro(&self) -> String {
+        match &self.chords {
+            Some(lines) => lines
+                .iter()
+                .map(ChordLine::to_chordpro)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => self.text.clone(),
+        }
+    }
+}
+
+/// Plain text without chords
+impl From<&str> for Part {
+    fn from(text: &str) -> Self {
+        Self {
+            text: text.to_owned(),
+            chords: None,
+        }
+    }
+}
+
+impl From<String> for Part {
+    fn from(text: String) -> Self {
+        Self { text, chords: None }
+    }
+}
+
+/// Serialization of a part
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum PartJson {
+    Plain(String),
+    Text {
+        text: String,
+        /// Text is ChordPro
+        #[serde(default)]
+        chords: bool,
+    },
+}
+
+impl Serialize for Part {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.chords {
+            Some(_) => PartJson::Text {
+                text: self.to_chordpro(),
+                chords: true,
+            },
+            None => PartJson::Plain(self.text.clone()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Part {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PartJson::deserialize(deserializer).map(|part| match part {
+            PartJson::Text { text, chords: true } => Part::chordpro(&text),
+            PartJson::Plain(text) | PartJson::Text { text, .. } => Part::from(text),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Chord, Part, parse};
+
+    fn chord(offset: usize, name: &str) -> Chord {
+        Chord {
+            offset,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn alignment() {
+        let lines = parse("[G]Amazing [C]grace, how [G]sweet the [D7]sound");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].lyrics, "Amazing grace, how sweet the sound");
+        assert_eq!(
+            lines[0].chords,
+            vec![
+                chord(0, "G"),
+                chord(8, "C"),
+                chord(19, "G"),
+                chord(29, "D7")
+            ]
+        );
+        for chord in &lines[0].chords {
+            assert_eq!(
+                lines[0]
+                    .chord_row()
+                    .chars()
+                    .skip(chord.offset)
+                    .collect::<String>()[..1],
+                chord.name[..1]
+            );
+        }
+    }
+
+    #[test]
+    fn multibyte_offsets() {
+        let lines = parse("Geloofd zij [Am]één");
+        assert_eq!(lines[0].chords, vec![chord(12, "Am")]);
+        assert_eq!(lines[0].chord_row(), "            Am");
+    }
+
+    #[test]
+    fn mid_word_and_end_of_line() {
+        let lines = parse("A[D]ma[G]zing\ngrace[A]");
+        assert_eq!(lines[0].lyrics, "Amazing");
+        assert_eq!(lines[0].chords, vec![chord(1, "D"), chord(3, "G")]);
+        assert_eq!(lines[0].chord_row(), " D G");
+        assert_eq!(lines[1].lyrics, "grace");
+        assert_eq!(lines[1].chords, vec![chord(5, "A")]);
+    }
+
+    #[test]
+    fn overlapping_chords() {
+        let lines = parse("[Gsus4]A[G]mazing");
+        assert_eq!(lines[0].chord_row(), "Gsus4 G");
+    }
+
+    #[test]
+    fn unclosed_bracket() {
+        let lines = parse("Refrein [2x");
+        assert_eq!(lines[0].lyrics, "Refrein [2x");
+        assert!(lines[0].chords.is_empty());
+    }
+
+    #[test]
+    fn part_roundtrip() {
+        let text = "[G]Amazing [C]grace\nhow [G]sweet the sound[D]";
+        let part = Part::chordpro(text);
+        assert_eq!(part.text, "Amazing grace\nhow sweet the sound");
+        assert_eq!(part.to_chordpro(), text);
+        let json = serde_json::to_string(&part).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"text":{},"chords":true}}"#,
+                serde_json::to_string(text).unwrap()
+            )
+        );
+        assert_eq!(serde_json::from_str::<Part>(&json).unwrap(), part);
+
+        let plain = Part::chordpro("Amazing grace");
+        assert!(plain.chords.is_none());
+        assert_eq!(serde_json::to_string(&plain).unwrap(), r#""Amazing grace""#);
+    }
+
+    #[test]
+    fn plain_brackets() {
+        let part = serde_json::from_str::<Part>(r#""Refrein [2x]""#).unwrap();
+        assert_eq!(part, Part::from("Refrein [2x]"));
+        assert_eq!(serde_json::to_string(&part).unwrap(), r#""Refrein [2x]""#);
+        let part = serde_json::from_str::<Part>(r#"{"text":"Refrein [2x]"}"#).unwrap();
+        assert!(part.chords.is_none());
+        assert_eq!(part.text, "Refrein [2x]");
+    }
+}
@@ -0,0 +1,44 @@
+use crate::{Align, Command, Theme};
+use serde::{Deserialize, Serialize};
+
+/// Keys of the json objects accepted on the command characteristic
+pub const JSON_COMMANDS: &[&str] = &["setFontSize", "theme", "align", "goto", "autoFit"];
+
+/// Wire format of the commands with an argument
+///
+/// Kept apart from [`Command`] so that the serialization of messages does not change.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) enum JsonCommand {
+    SetFontSize(u32),
+    Theme(Theme),
+    Align(Align),
+    Goto(usize),
+    AutoFit(bool),
+}
+
+impl From<JsonCommand> for Command {
+    fn from(json_command: JsonCommand) -> Self {
+        match json_command {
+            JsonCommand::SetFontSize(font_size) => Command::SetFontSize(font_size),
+            JsonCommand::Theme(theme) => Command::Theme(theme),
+            JsonCommand::Align(align) => Command::Align(align),
+            JsonCommand::Goto(index) => Command::Goto(index),
+            JsonCommand::AutoFit(auto_fit) => Command::AutoFit(auto_fit),
+        }
+    }
+}
+
+impl TryFrom<Command> for JsonCommand {
+    type Error = Command;
+    fn try_from(command: Command) -> Result<Self, Self::Error> {
+        match command {
+            Command::SetFontSize(font_size) => Ok(JsonCommand::SetFontSize(font_size)),
+            Command::Theme(theme) => Ok(JsonCommand::Theme(theme)),
+            Command::Align(align) => Ok(JsonCommand::Align(align)),
+            Command::Goto(index) => Ok(JsonCommand::Goto(index)),
+            Command::AutoFit(auto_fit) => Ok(JsonCommand::AutoFit(auto_fit)),
+            command => Err(command),
+        }
+    }
+}
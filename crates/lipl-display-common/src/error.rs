@@ -35,4 +35,31 @@ pub enum Error {
 
     #[error("Parsing Gatt Characteristic value failed")]
     GattCharaceristicValueParsing(String),
+
+    #[error("Invalid frame: {0}")]
+    Framing(String),
+
+    #[error("Invalid offset {0} in long write")]
+    InvalidOffset(usize),
+
+    #[error("Invalid config: {0}")]
+    Config(String),
+
+    #[error("Write not signed")]
+    Unsigned,
+
+    #[error("Invalid signature")]
+    Signature,
+
+    #[error("Replayed write with counter {0}")]
+    Replayed(u64),
+
+    #[error("Value of {length} characters longer than {max}")]
+    TooLong { length: usize, max: usize },
+
+    #[error("Too many writes")]
+    RateLimited,
+
+    #[error("{0} not allowed")]
+    NotAllowed(String),
 }
@@ -0,0 +1,90 @@
+//! Auto fit: the largest font size at which a text fits its area
+
+/// Default for the smallest font size auto fit may choose
+pub const DEFAULT_MIN_FONT_SIZE: f32 = 12.0;
+/// Largest font size auto fit may choose
+pub const MAX_FONT_SIZE: f32 = 100.0;
+
+/// Largest whole font size between min and max for which fits returns true
+///
+/// Returns min when the text does not even fit at min,
+/// assuming that a text fitting at some size also fits at every smaller size.
+///
+/// # Example
+///
+/// ```
+/// use lipl_display_common::fit::fit_font_size;
+/// // Ten characters of half the font size wide on a line of 300 pixels
+/// assert_eq!(fit_font_size(12.0, 100.0, |size| 10.0 * size / 2.0 <= 300.0), 60.0);
+/// ```
+pub fn fit_font_size(min: f32, max: f32, fits: impl Fn(f32) -> bool) -> f32 {
+    let mut low = min.ceil().max(1.0) as u32;
+    let mut high = max.floor().max(low as f32) as u32;
+    if !fits(low as f32) {
+        return min;
+    }
+    while low < high {
+        let middle = (low + high).div_ceil(2);
+        if fits(middle as f32) {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    low as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::fit_font_size;
+
+    /// Greedy word wrap for a font with characters half the font size wide
+    fn line_count(text: &str, size: f32, width: f32) -> usize {
+        let chars_per_line = ((width / (size / 2.0)) as usize).max(1);
+        text.lines()
+            .map(|line| {
+                let mut lines = 1;
+                let mut length = 0;
+                for word in line.split_whitespace() {
+                    let word_length = word.chars().count();
+                    if length > 0 && length + 1 + word_length > chars_per_line {
+                        lines += 1;
+                        length = word_length;
+                    } else {
+                        length += word_length + usize::from(length > 0);
+                    }
+                }
+                lines
+            })
+            .sum()
+    }
+
+    fn fits(text: &str, width: f32, height: f32) -> impl Fn(f32) -> bool {
+        move |size| line_count(text, size, width) as f32 * size * 1.2 <= height
+    }
+
+    #[test]
+    fn long_verse_shrinks() {
+        let verse = "Amazing grace, how sweet the sound that saved a wretch like me\n".repeat(4);
+        let short = fit_font_size(12.0, 100.0, fits("Amen", 800.0, 480.0));
+        let long = fit_font_size(12.0, 100.0, fits(&verse, 800.0, 480.0));
+        assert_eq!(short, 100.0);
+        assert!(long < short);
+        assert!(fits(&verse, 800.0, 480.0)(long));
+        assert!(!fits(&verse, 800.0, 480.0)(long + 1.0));
+    }
+
+    #[test]
+    fn minimum() {
+        let text = "word ".repeat(1000);
+        assert_eq!(fit_font_size(12.0, 100.0, fits(&text, 800.0, 480.0)), 12.0);
+    }
+
+    #[test]
+    fn resize_grows() {
+        let verse = "Amazing grace, how sweet the sound that saved a wretch like me";
+        let small = fit_font_size(12.0, 100.0, fits(verse, 400.0, 300.0));
+        let large = fit_font_size(12.0, 100.0, fits(verse, 1920.0, 1080.0));
+        assert!(large > small);
+    }
+}
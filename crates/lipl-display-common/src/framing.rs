@@ -0,0 +1,297 @@
+//! Framing of values that do not fit in a single characteristic write
+//!
+//! A framed write starts with [`FRAME_MARKER`], a byte that never occurs in utf-8,
+//! followed by a sequence number, flags and a part of the value.
+//! The first frame of a value has no [`FLAG_CONTINUATION`], the last frame has [`FLAG_FINAL`].
+//! Writes without the marker are complete values, as written by older controllers.
+//!
+//! Long writes, where the gatt server passes an offset with every part of a value,
+//! are buffered with [`Reassembler::prepare`] and complete with [`Reassembler::execute`].
+//! A gatt server does not tell which part is the last one,
+//! so a backend executes the long write when no part follows within [`LONG_WRITE_DELAY`].
+//!
+//! # Example
+//!
+//! ```
+//! use lipl_display_common::framing::{Reassembler, fragment};
+//! let text = "Amazing grace, how sweet the sound";
+//! let mut reassembler = Reassembler::default();
+//! let received = fragment(text.as_bytes(), 20)
+//!     .iter()
+//!     .filter_map(|frame| reassembler.push(0, frame).unwrap())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(received, vec![text.as_bytes().to_vec()]);
+//! ```
+
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// First byte of a framed write
+pub const FRAME_MARKER: u8 = 0xFF;
+/// Number of bytes preceding the payload in a frame
+pub const FRAME_HEADER_SIZE: usize = 3;
+/// Frame continues a value started by an earlier frame
+pub const FLAG_CONTINUATION: u8 = 0x01;
+/// Frame completes the value
+pub const FLAG_FINAL: u8 = 0x02;
+/// Largest value accepted after reassembly
+pub const MAX_PAYLOAD_SIZE: usize = 64 * 1024;
+/// Time after the last part of a long write before it is executed
+pub const LONG_WRITE_DELAY: Duration = Duration::from_millis(100);
+
+/// Split value in frames of at most max_size bytes, header included
+///
+/// # Panics
+///
+/// Panics if max_size leaves no room for payload
+pub fn fragment(value: &[u8], max_size: usize) -> Vec<Vec<u8>> {
+    assert!(max_size > FRAME_HEADER_SIZE, "Frame size too small");
+    let chunks = value
+        .chunks(max_size - FRAME_HEADER_SIZE)
+        .collect::<Vec<_>>();
+    let count = chunks.len().max(1);
+    (0..count)
+        .map(|index| {
+            let mut flags = 0;
+            if index > 0 {
+                flags |= FLAG_CONTINUATION;
+            }
+            if index == count - 1 {
+                flags |= FLAG_FINAL;
+            }
+            let mut frame = vec![FRAME_MARKER, index as u8, flags];
+            frame.extend_from_slice(chunks.get(index).copied().unwrap_or_default());
+            frame
+        })
+        .collect()
+}
+
+/// Reassembles the writes on a single characteristic
+#[derive(Debug, Default)]
+pub struct Reassembler {
+    buffer: Vec<u8>,
+    next_sequence: Option<u8>,
+    long_write: Option<Vec<u8>>,
+}
+
+impl Reassembler {
+    /// Handle a write with offset
+    ///
+    /// Returns the value when it is complete.
+    /// A write with an offset is a part of a long write, buffered as with [`Reassembler::prepare`].
+    pub fn push(&mut self, offset: usize, write: &[u8]) -> Result<Option<Vec<u8>>> {
+        if offset > 0 {
+            return self.prepare(offset, write).map(|_| None);
+        }
+
+        self.long_write = None;
+        match write.split_first() {
+            Some((&FRAME_MARKER, frame)) => self.push_frame(frame),
+            _ => {
+                self.reset();
+                Ok(Some(write.to_vec()))
+            }
+        }
+    }
+
+    /// Buffer a part of a long write, offset 0 starts a new value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::framing::Reassembler;
+    /// let mut reassembler = Reassembler::default();
+    /// reassembler.prepare(0, b"Amazing ").unwrap();
+    /// reassembler.prepare(8, b"grace").unwrap();
+    /// assert_eq!(reassembler.execute(), Some(b"Amazing grace".to_vec()));
+    /// assert_eq!(reassembler.execute(), None);
+    /// ```
+    pub fn prepare(&mut self, offset: usize, write: &[u8]) -> Result<()> {
+        if offset == 0 {
+            self.reset();
+            self.long_write = Some(vec![]);
+        }
+        let Some(buffer) = self
+            .long_write
+            .as_mut()
+            .filter(|buffer| buffer.len() == offset)
+        else {
+            self.reset();
+            self.long_write = None;
+            return Err(Error::InvalidOffset(offset));
+        };
+        if offset + write.len() > MAX_PAYLOAD_SIZE {
+            self.long_write = None;
+            return Err(Error::Framing(format!(
+                "Value exceeds {MAX_PAYLOAD_SIZE} bytes"
+            )));
+        }
+        buffer.extend_from_slice(write);
+        Ok(())
+    }
+
+    /// Value of the long write that was prepared, once all parts are written
+    pub fn execute(&mut self) -> Option<Vec<u8>> {
+        self.long_write.take()
+    }
+
+    fn push_frame(&mut self, frame: &[u8]) -> Result<Option<Vec<u8>>> {
+        let [sequence, flags, payload @ ..] = frame else {
+            self.reset();
+            return Err(Error::Framing("Incomplete header".to_owned()));
+        };
+
+        if flags & FLAG_CONTINUATION == 0 {
+            self.buffer.clear();
+        } else if self.next_sequence != Some(*sequence) {
+            let expected = self.next_sequence;
+            self.reset();
+            return Err(Error::Framing(match expected {
+                Some(expected) => format!("Expected sequence {expected}, received {sequence}"),
+                None => format!("Continuation {sequence} without first frame"),
+            }));
+        }
+
+        if self.buffer.len() + payload.len() > MAX_PAYLOAD_SIZE {
+            self.reset();
+            return Err(Error::Framing(format!(
+                "Value exceeds {MAX_PAYLOAD_SIZE} bytes"
+            )));
+        }
+        self.buffer.extend_from_slice(payload);
+        if flags & FLAG_FINAL == 0 {
+            self.next_sequence = Some(sequence.wrapping_add(1));
+            Ok(None)
+        } else {
+            self.next_sequence = None;
+            Ok(Some(std::mem::take(&mut self.buffer)))
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.next_sequence = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        FLAG_CONTINUATION, FLAG_FINAL, FRAME_HEADER_SIZE, FRAME_MARKER, MAX_PAYLOAD_SIZE,
+        Reassembler, fragment,
+    };
+    use crate::Error;
+
+    fn reassemble(reassembler: &mut Reassembler, frames: &[Vec<u8>]) -> Vec<Vec<u8>> {
+        frames
+            .iter()
+            .filter_map(|frame| reassembler.push(0, frame).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn fragment_headers() {
+        let frames = fragment(b"abcdefgh", 6);
+        assert_eq!(
+            frames,
+            vec![
+                vec![FRAME_MARKER, 0, 0, b'a', b'b', b'c'],
+                vec![FRAME_MARKER, 1, FLAG_CONTINUATION, b'd', b'e', b'f'],
+                vec![FRAME_MARKER, 2, FLAG_CONTINUATION | FLAG_FINAL, b'g', b'h'],
+            ]
+        );
+        assert_eq!(fragment(b"", 20), vec![vec![FRAME_MARKER, 0, FLAG_FINAL]]);
+    }
+
+    #[test]
+    fn long_text() {
+        let text = "Couplet 1\n".repeat(500);
+        let mut reassembler = Reassembler::default();
+        let frames = fragment(text.as_bytes(), 20);
+        assert!(frames.len() > u8::MAX as usize);
+        assert_eq!(
+            reassemble(&mut reassembler, &frames),
+            vec![text.as_bytes().to_vec()]
+        );
+    }
+
+    #[test]
+    fn unframed() {
+        let mut reassembler = Reassembler::default();
+        assert_eq!(reassembler.push(0, b"d").unwrap(), Some(b"d".to_vec()));
+        assert_eq!(reassembler.push(0, b"").unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn out_of_order() {
+        let mut reassembler = Reassembler::default();
+        let frames = fragment(b"abcdefgh", 6);
+        assert!(reassembler.push(0, &frames[0]).unwrap().is_none());
+        assert!(reassembler.push(0, &frames[2]).is_err());
+        assert!(reassembler.push(0, &frames[1]).is_err());
+        assert_eq!(
+            reassemble(&mut reassembler, &frames),
+            vec![b"abcdefgh".to_vec()]
+        );
+    }
+
+    #[test]
+    fn multibyte_split() {
+        let text = "Café";
+        let mut reassembler = Reassembler::default();
+        // The two bytes of é end up in different frames
+        let frames = fragment(text.as_bytes(), 7);
+        assert_eq!(frames[1][FRAME_HEADER_SIZE..], [0xA9]);
+        assert!(reassembler.push(0, &frames[0]).unwrap().is_none());
+        assert_eq!(
+            reassembler.push(0, &frames[1]).unwrap(),
+            Some(text.as_bytes().to_vec())
+        );
+
+        // The same split in the parts of a long write
+        reassembler.prepare(0, &text.as_bytes()[..4]).unwrap();
+        assert_eq!(reassembler.push(4, &text.as_bytes()[4..]).unwrap(), None);
+        assert_eq!(reassembler.execute(), Some(text.as_bytes().to_vec()));
+    }
+
+    #[test]
+    fn long_write() {
+        let mut reassembler = Reassembler::default();
+        reassembler.prepare(0, b"abc").unwrap();
+        reassembler.prepare(3, b"def").unwrap();
+        // Offset 0 starts again
+        reassembler.prepare(0, b"ghi").unwrap();
+        reassembler.prepare(3, b"jkl").unwrap();
+        assert_eq!(reassembler.execute(), Some(b"ghijkl".to_vec()));
+
+        // A gap in the offsets discards the parts
+        reassembler.prepare(0, b"abc").unwrap();
+        assert!(matches!(
+            reassembler.prepare(4, b"def"),
+            Err(Error::InvalidOffset(4))
+        ));
+        assert_eq!(reassembler.execute(), None);
+        assert!(matches!(
+            reassembler.push(3, b"def"),
+            Err(Error::InvalidOffset(3))
+        ));
+
+        // A complete write discards an unfinished long write
+        reassembler.prepare(0, b"abc").unwrap();
+        assert_eq!(reassembler.push(0, b"d").unwrap(), Some(b"d".to_vec()));
+        assert_eq!(reassembler.execute(), None);
+    }
+
+    #[test]
+    fn too_large() {
+        let mut reassembler = Reassembler::default();
+        let frames = fragment(&vec![b'a'; MAX_PAYLOAD_SIZE + 1], 512);
+        assert!(
+            frames
+                .iter()
+                .map(|frame| reassembler.push(0, frame))
+                .any(|result| result.is_err())
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    Command, Error, JSON_COMMANDS, MESSAGES, Result,
+    framing::MAX_PAYLOAD_SIZE,
+    validate::{MAX_PART_LENGTH, MAX_STATUS_LENGTH},
+};
+use serde::{Deserialize, Serialize};
+
+/// Version of the protocol spoken on the display service
+///
+/// Incremented when the meaning of an existing characteristic value changes.
+/// Adding commands or optional features does not change the protocol version.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Optional feature of a display
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Feature {
+    /// Framed writes, see [`crate::framing`]
+    Chunking,
+    /// Long writes with offset
+    LongWrite,
+    /// Notifications on the screen characteristic
+    Notify,
+    /// Playlist characteristic with local navigation
+    Playlist,
+}
+
+/// Value of the info characteristic
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Info {
+    pub protocol_version: u32,
+    pub version: String,
+    pub frontend: String,
+    pub commands: Vec<Command>,
+    pub json_commands: Vec<String>,
+    /// Largest value in bytes after reassembly, as a playlist with all its parts
+    pub max_payload_size: usize,
+    /// Longest text of a part in characters, see [`crate::validate`]
+    pub max_part_length: usize,
+    /// Longest status or playlist title in characters
+    pub max_status_length: usize,
+    pub features: Vec<Feature>,
+}
+
+impl Info {
+    /// Info for a display with the given frontend name
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::{Command, Info, PROTOCOL_VERSION};
+    /// let info = Info::new("lipl-display-egui");
+    /// assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+    /// assert!(info.commands.contains(&Command::Dark));
+    /// ```
+    pub fn new(frontend: impl Into<String>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            frontend: frontend.into(),
+            commands: MESSAGES.iter().map(|message| message.1.clone()).collect(),
+            json_commands: JSON_COMMANDS.iter().map(|&s| s.to_owned()).collect(),
+            max_payload_size: MAX_PAYLOAD_SIZE,
+            max_part_length: MAX_PART_LENGTH,
+            max_status_length: MAX_STATUS_LENGTH,
+            features: vec![
+                Feature::Chunking,
+                Feature::LongWrite,
+                Feature::Notify,
+                Feature::Playlist,
+            ],
+        }
+    }
+
+    /// Json serialization as served by the info characteristic
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|_| Error::JsonSerialization)
+    }
+}
+
+impl Default for Info {
+    /// Info with the name of the running executable as frontend name
+    fn default() -> Self {
+        Self::new(
+            std::env::current_exe()
+                .ok()
+                .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Feature, Info};
+
+    #[test]
+    fn json() {
+        let info = Info::new("lipl-display-femtovg");
+        let json = info.to_json().unwrap();
+        assert!(json.starts_with(r#"{"protocolVersion":1,"version":""#));
+        assert!(json.contains(r#""frontend":"lipl-display-femtovg""#));
+        assert!(json.contains(
+            r#""commands":["Dark","Light","Increase","Decrease","Wait","Exit","Poweroff","Next","Previous"]"#
+        ));
+        assert!(
+            json.contains(r#""jsonCommands":["setFontSize","theme","align","goto","autoFit"]"#)
+        );
+        assert!(json.contains(
+            r#""maxPayloadSize":65536,"maxPartLength":8192,"maxStatusLength":256,"features":["chunking","longWrite","notify","playlist"]"#
+        ));
+        assert_eq!(serde_json::from_str::<Info>(&json).unwrap(), info);
+        assert!(info.features.contains(&Feature::Notify));
+    }
+}
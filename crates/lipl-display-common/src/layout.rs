@@ -0,0 +1,403 @@
+//! Toolkit independent layout of a screen
+//!
+//! A frontend measures text with its own fonts through [`Measure`] and
+//! draws the positioned text of the resulting [`Layout`].
+//! Margins, line breaks, the status area and what happens to text
+//! that does not fit are decided here, so every frontend shows a part the same way.
+//!
+//! # Example
+//!
+//! ```
+//! use lipl_display_common::{
+//!     HandleMessage, LiplScreen, Message,
+//!     layout::{Layout, Monospace, Viewport},
+//! };
+//! let mut screen = LiplScreen::new(true, 20.0);
+//! screen.handle_message(Message::Part("Amazing grace".into()));
+//! let layout = Layout::new(&screen, &Viewport::new(800.0, 600.0), &Monospace::default());
+//! assert_eq!(layout.lines[0].text, "Amazing grace");
+//! assert_eq!((layout.lines[0].x, layout.lines[0].y), (40.0, 30.0));
+//! assert!(!layout.overflow);
+//! ```
+
+use crate::{
+    Align, ChordLine, LiplScreen,
+    chordpro::Chord,
+    fit::{DEFAULT_MIN_FONT_SIZE, MAX_FONT_SIZE, fit_font_size},
+};
+
+/// Margin around the text and status areas, as a fraction of the viewport
+pub const MARGIN: f32 = 0.05;
+/// Font size of the status line relative to the font size of the screen
+pub const STATUS_FONT_FACTOR: f32 = 0.7;
+
+/// Text measurement with the fonts of a frontend
+pub trait Measure {
+    /// Width of a single line of text
+    fn text_width(&self, text: &str, font_size: f32) -> f32;
+    /// Distance between the tops of two consecutive lines
+    fn line_height(&self, font_size: f32) -> f32;
+}
+
+/// Measurement for a monospaced font, relative to the font size
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Monospace {
+    pub advance: f32,
+    pub line_height: f32,
+}
+
+impl Default for Monospace {
+    fn default() -> Self {
+        Self {
+            advance: 0.5,
+            line_height: 1.2,
+        }
+    }
+}
+
+impl Measure for Monospace {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        text.chars().count() as f32 * self.advance * font_size
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        self.line_height * font_size
+    }
+}
+
+/// Space available to a frontend
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub width: f32,
+    pub height: f32,
+    /// Smallest font size auto fit may choose
+    pub min_font_size: f32,
+}
+
+impl Viewport {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            min_font_size: DEFAULT_MIN_FONT_SIZE,
+        }
+    }
+}
+
+/// Area of the viewport, with the origin at the top left
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+}
+
+/// Text to draw with its top left corner at x and y
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    pub x: f32,
+    pub y: f32,
+    pub text: String,
+}
+
+/// Positioned text of a screen
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+    /// Font size of lines and chords, chosen by auto fit if the screen asks for it
+    pub font_size: f32,
+    pub text_area: Rect,
+    pub lines: Vec<Span>,
+    pub chords: Vec<Span>,
+    pub status_font_size: f32,
+    pub status_area: Rect,
+    pub status: Span,
+    /// Part of the text is left out or wider than the text area
+    pub overflow: bool,
+}
+
+/// Line after wrapping, with the chords moved along with their syllables
+struct Row {
+    lyrics: String,
+    chords: Vec<Chord>,
+}
+
+impl Layout {
+    pub fn new(screen: &LiplScreen, viewport: &Viewport, measure: &impl Measure) -> Self {
+        let margin_x = MARGIN * viewport.width;
+        let margin_y = MARGIN * viewport.height;
+        let status_font_size = screen.font_size * STATUS_FONT_FACTOR;
+        let status_height = measure.line_height(status_font_size);
+        let status_area = Rect {
+            x: margin_x,
+            y: viewport.height - margin_y - status_height,
+            width: viewport.width - 2.0 * margin_x,
+            height: status_height,
+        };
+        let text_area = Rect {
+            x: margin_x,
+            y: margin_y,
+            width: status_area.width,
+            height: (status_area.y - 2.0 * margin_y).max(0.0),
+        };
+
+        let lines = match &screen.chords {
+            Some(chord_lines) => chord_lines.clone(),
+            None => screen
+                .text
+                .lines()
+                .map(|line| ChordLine {
+                    lyrics: line.to_owned(),
+                    chords: vec![],
+                })
+                .collect(),
+        };
+        let height = |rows: &[Row], font_size: f32| {
+            rows.iter().map(Row::line_count).sum::<usize>() as f32 * measure.line_height(font_size)
+        };
+        let font_size = if screen.auto_fit {
+            fit_font_size(viewport.min_font_size, MAX_FONT_SIZE, |font_size| {
+                let rows = wrap_lines(&lines, text_area.width, font_size, measure);
+                height(&rows, font_size) <= text_area.height
+                    && rows
+                        .iter()
+                        .all(|row| measure.text_width(&row.lyrics, font_size) <= text_area.width)
+            })
+        } else {
+            screen.font_size
+        };
+
+        let mut layout = Self {
+            font_size,
+            text_area,
+            status_font_size,
+            status_area,
+            status: Span {
+                x: status_area.x,
+                y: status_area.y,
+                text: screen.status.clone(),
+            },
+            ..Default::default()
+        };
+        let line_height = measure.line_height(font_size);
+        let space_width = measure.text_width(" ", font_size);
+        let mut y = text_area.y;
+        for row in wrap_lines(&lines, text_area.width, font_size, measure) {
+            if y + row.line_count() as f32 * line_height > text_area.bottom() {
+                layout.overflow = true;
+                break;
+            }
+            let lyrics_width = measure.text_width(&row.lyrics, font_size);
+            layout.overflow |= lyrics_width > text_area.width;
+            let x = match screen.align {
+                Align::Left => text_area.x,
+                Align::Center => text_area.x + (text_area.width - lyrics_width) / 2.0,
+                Align::Right => text_area.x + text_area.width - lyrics_width,
+            };
+            if !row.chords.is_empty() {
+                let mut chord_x = x;
+                for chord in &row.chords {
+                    let prefix = row.lyrics.chars().take(chord.offset).collect::<String>();
+                    chord_x = chord_x.max(x + measure.text_width(&prefix, font_size));
+                    layout.chords.push(Span {
+                        x: chord_x,
+                        y,
+                        text: chord.name.clone(),
+                    });
+                    chord_x += measure.text_width(&chord.name, font_size) + space_width;
+                }
+                y += line_height;
+            }
+            layout.lines.push(Span {
+                x,
+                y,
+                text: row.lyrics,
+            });
+            y += line_height;
+        }
+        layout
+    }
+}
+
+impl Row {
+    fn line_count(&self) -> usize {
+        if self.chords.is_empty() { 1 } else { 2 }
+    }
+}
+
+fn wrap_lines(lines: &[ChordLine], width: f32, font_size: f32, measure: &impl Measure) -> Vec<Row> {
+    lines
+        .iter()
+        .flat_map(|line| wrap(line, width, font_size, measure))
+        .collect()
+}
+
+/// Break a line at whitespace into rows no wider than width
+///
+/// A word wider than width gets a row of its own.
+fn wrap(line: &ChordLine, width: f32, font_size: f32, measure: &impl Measure) -> Vec<Row> {
+    let chars = line.lyrics.chars().collect::<Vec<_>>();
+    let text = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+
+    let mut ranges = vec![];
+    let (mut start, mut end, mut index) = (0, 0, 0);
+    while index < chars.len() {
+        while index < chars.len() && chars[index].is_whitespace() {
+            index += 1;
+        }
+        let word_start = index;
+        while index < chars.len() && !chars[index].is_whitespace() {
+            index += 1;
+        }
+        if word_start == index {
+            break;
+        }
+        if end > start && measure.text_width(&text(start, index), font_size) > width {
+            ranges.push((start, end));
+            start = word_start;
+        }
+        end = index;
+    }
+    ranges.push((start, end));
+
+    let mut rows = ranges
+        .iter()
+        .map(|&(start, end)| Row {
+            lyrics: text(start, end),
+            chords: vec![],
+        })
+        .collect::<Vec<_>>();
+    for chord in &line.chords {
+        let row = ranges
+            .iter()
+            .rposition(|&(start, _)| start <= chord.offset)
+            .unwrap_or_default();
+        let (start, end) = ranges[row];
+        rows[row].chords.push(Chord {
+            offset: chord.offset.min(end) - start,
+            name: chord.name.clone(),
+        });
+    }
+    rows
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Layout, Monospace, Viewport};
+    use crate::{Align, Command, HandleMessage, LiplScreen, Message, Part};
+
+    fn screen(text: &str, font_size: f32) -> LiplScreen {
+        let mut screen = LiplScreen::new(true, font_size);
+        screen.handle_message(Message::Part(Part::chordpro(text)));
+        screen.handle_message(Message::Status("1/3".to_owned()));
+        screen
+    }
+
+    fn texts(layout: &Layout) -> Vec<&str> {
+        layout.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn areas() {
+        let layout = Layout::new(
+            &screen("", 20.0),
+            &Viewport::new(800.0, 600.0),
+            &Monospace::default(),
+        );
+        assert_eq!(layout.status_font_size, 14.0);
+        assert_eq!(layout.status_area.x, 40.0);
+        assert_eq!(layout.status_area.bottom(), 570.0);
+        assert_eq!(layout.status.text, "1/3");
+        assert_eq!(layout.text_area.y, 30.0);
+        assert_eq!(layout.text_area.width, 720.0);
+        assert_eq!(layout.text_area.bottom() + 30.0, layout.status_area.y);
+    }
+
+    #[test]
+    fn line_breaks() {
+        // 72 characters per line
+        let layout = Layout::new(
+            &screen(
+                "Amazing grace, how sweet the sound that saved a wretch like me. I once was lost, but now am found\nWas blind",
+                20.0,
+            ),
+            &Viewport::new(800.0, 600.0),
+            &Monospace::default(),
+        );
+        assert_eq!(
+            texts(&layout),
+            vec![
+                "Amazing grace, how sweet the sound that saved a wretch like me. I once",
+                "was lost, but now am found",
+                "Was blind"
+            ]
+        );
+        assert_eq!(layout.lines[1].y - layout.lines[0].y, 24.0);
+    }
+
+    #[test]
+    fn alignment() {
+        let mut screen = screen("Amen", 20.0);
+        let viewport = Viewport::new(800.0, 600.0);
+        screen.handle_message(Message::Command(Command::Align(Align::Center)));
+        let layout = Layout::new(&screen, &viewport, &Monospace::default());
+        assert_eq!(layout.lines[0].x, 380.0);
+        screen.handle_message(Message::Command(Command::Align(Align::Right)));
+        let layout = Layout::new(&screen, &viewport, &Monospace::default());
+        assert_eq!(layout.lines[0].x, 720.0);
+    }
+
+    #[test]
+    fn overflow() {
+        let text = "line\n".repeat(30);
+        let layout = Layout::new(
+            &screen(&text, 20.0),
+            &Viewport::new(800.0, 600.0),
+            &Monospace::default(),
+        );
+        assert!(layout.overflow);
+        let last = layout.lines.last().unwrap();
+        assert!(last.y + 24.0 <= layout.text_area.bottom());
+        assert_eq!(layout.lines.len(), 20);
+    }
+
+    #[test]
+    fn auto_fit() {
+        let text = "line\n".repeat(30);
+        let mut screen = screen(&text, 20.0);
+        screen.handle_message(Message::Command(Command::AutoFit(true)));
+        let layout = Layout::new(&screen, &Viewport::new(800.0, 600.0), &Monospace::default());
+        assert!(!layout.overflow);
+        assert_eq!(layout.lines.len(), 30);
+        assert!(layout.font_size < 20.0);
+        assert_eq!(layout.status_font_size, 14.0);
+    }
+
+    #[test]
+    fn chords_follow_wrapped_syllables() {
+        let layout = Layout::new(
+            &screen("[G]Amazing [C]grace how [D]sweet", 20.0),
+            &Viewport::new(200.0, 600.0),
+            &Monospace::default(),
+        );
+        // 18 characters per line
+        assert_eq!(texts(&layout), vec!["Amazing grace how", "sweet"]);
+        let chords = layout
+            .chords
+            .iter()
+            .map(|chord| (chord.text.as_str(), chord.x, chord.y))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chords,
+            vec![("G", 10.0, 30.0), ("C", 90.0, 30.0), ("D", 10.0, 78.0)]
+        );
+        assert_eq!(layout.lines[0].y, 54.0);
+        assert_eq!(layout.lines[1].y, 102.0);
+    }
+}
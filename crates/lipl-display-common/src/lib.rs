@@ -5,10 +5,28 @@ use std::convert::TryFrom;
 use std::str::FromStr;
 use uuid::{Uuid, uuid};
 
+pub mod access;
+pub mod chordpro;
+mod command;
 mod error;
+pub mod fit;
+pub mod framing;
+mod info;
+pub mod layout;
+mod playlist;
+mod security;
+pub mod signed;
+mod source;
+pub mod validate;
 
+pub use chordpro::{ChordLine, Part};
+pub use command::JSON_COMMANDS;
 /// Error type
 pub use error::Error;
+pub use info::{Feature, Info, PROTOCOL_VERSION};
+pub use playlist::{Pager, Playlist};
+pub use security::Security;
+pub use source::{BoxMessageSource, Injector, MessageSource};
 pub type Result<T> = std::result::Result<T, Error>;
 
 pub trait HandleMessage {
@@ -28,18 +46,27 @@ pub const CHARACTERISTIC_TEXT_UUID: Uuid = uuid!("04973569-c039-4ce9-ad96-861589
 pub const CHARACTERISTIC_STATUS_UUID: Uuid = uuid!("61a8cb7f-d4c1-49b7-a3cf-f2c69dbb7aeb");
 /// Uuid identifying the command characteristic on the gatt peripheral
 pub const CHARACTERISTIC_COMMAND_UUID: Uuid = uuid!("da35e0b2-7864-49e5-aa47-8050d1cc1484");
-pub const SERVICE: (Uuid, [Uuid; 3]) = (
+/// Uuid identifying the readable and notifying screen characteristic on the gatt peripheral
+pub const CHARACTERISTIC_SCREEN_UUID: Uuid = uuid!("c571e919-5800-4a97-8be6-ba5bcdde709f");
+/// Uuid identifying the read only info characteristic on the gatt peripheral
+pub const CHARACTERISTIC_INFO_UUID: Uuid = uuid!("3e5c1a8d-6b0f-4f72-9d47-2b9e8c61f0a4");
+/// Uuid identifying the playlist characteristic on the gatt peripheral
+pub const CHARACTERISTIC_PLAYLIST_UUID: Uuid = uuid!("8d2b6f0e-91c4-4e3a-b5d7-4f1a0c9e6b23");
+pub const SERVICE: (Uuid, [Uuid; 6]) = (
     SERVICE_UUID,
     [
         CHARACTERISTIC_TEXT_UUID,
         CHARACTERISTIC_STATUS_UUID,
         CHARACTERISTIC_COMMAND_UUID,
+        CHARACTERISTIC_SCREEN_UUID,
+        CHARACTERISTIC_INFO_UUID,
+        CHARACTERISTIC_PLAYLIST_UUID,
     ],
 );
 
 pub const WAIT_MESSAGE: &str = "Even geduld a.u.b. ...";
 
-pub const MESSAGES: &[(&str, Command); 7] = &[
+pub const MESSAGES: &[(&str, Command); 9] = &[
     ("d", Command::Dark),
     ("l", Command::Light),
     ("+", Command::Increase),
@@ -47,6 +74,8 @@ pub const MESSAGES: &[(&str, Command); 7] = &[
     ("?", Command::Wait),
     ("e", Command::Exit),
     ("o", Command::Poweroff),
+    ("n", Command::Next),
+    ("p", Command::Previous),
 ];
 
 pub trait BackgroundThread {
@@ -57,9 +86,10 @@ pub trait BackgroundThread {
 #[derive(Clone, Deserialize, Debug, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Message {
-    Part(String),
+    Part(Part),
     Status(String),
     Command(Command),
+    Playlist(Playlist),
 }
 
 impl Message {
@@ -78,9 +108,14 @@ impl std::fmt::Display for Message {
             f,
             "{}",
             match self {
-                Message::Part(text) => format!("Text: {text}"),
+                Message::Part(part) => format!("Text: {}", part.text),
                 Message::Status(status) => format!("Status: {status}"),
                 Message::Command(command) => format!("Command: {command}"),
+                Message::Playlist(playlist) => format!(
+                    "Playlist: {} ({} parts)",
+                    playlist.title.as_deref().unwrap_or_default(),
+                    playlist.parts.len()
+                ),
             }
         )
     }
@@ -96,21 +131,76 @@ pub enum Command {
     Light,
     Dark,
     Wait,
+    SetFontSize(u32),
+    Theme(Theme),
+    Align(Align),
+    Next,
+    Previous,
+    /// Show the part with this zero based index of the playlist
+    Goto(usize),
+    /// Switch auto fit of the font size on or off
+    AutoFit(bool),
+}
+
+/// Color scheme of the screen
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// Horizontal alignment of the text on the screen
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
 }
 
 impl std::fmt::Display for Command {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            MESSAGES.iter().find(|s| &s.1 == self).map(|s| s.0).unwrap()
-        )
+        match MESSAGES.iter().find(|s| &s.1 == self) {
+            Some(message) => write!(f, "{}", message.0),
+            None => match command::JsonCommand::try_from(self.clone()) {
+                Ok(json_command) => write!(
+                    f,
+                    "{}",
+                    serde_json::to_string(&json_command).map_err(|_| std::fmt::Error)?
+                ),
+                Err(_) => Err(std::fmt::Error),
+            },
+        }
     }
 }
 
 impl FromStr for Command {
     type Err = error::Error;
+
+    /// Parse a one character code from [`MESSAGES`] or a json object with an argument
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::{Align, Command, Theme};
+    /// assert_eq!("d".parse::<Command>().unwrap(), Command::Dark);
+    /// assert_eq!(r#"{"setFontSize": 48}"#.parse::<Command>().unwrap(), Command::SetFontSize(48));
+    /// assert_eq!(
+    ///     r#"{"theme": "high-contrast"}"#.parse::<Command>().unwrap(),
+    ///     Command::Theme(Theme::HighContrast)
+    /// );
+    /// assert_eq!(r#"{"align": "center"}"#.parse::<Command>().unwrap(), Command::Align(Align::Center));
+    /// ```
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.trim_start().starts_with('{') {
+            return serde_json::from_str::<command::JsonCommand>(s)
+                .map(Command::from)
+                .map_err(|error| error::Error::GattCharaceristicValueParsing(error.to_string()));
+        }
         MESSAGES
             .iter()
             .find(|t| t.0 == s)
@@ -121,6 +211,45 @@ impl FromStr for Command {
     }
 }
 
+/// Parse a name as used in json commands
+fn from_name<T: serde::de::DeserializeOwned>(s: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(s.to_owned()))
+        .map_err(|error| Error::GattCharaceristicValueParsing(error.to_string()))
+}
+
+impl FromStr for Theme {
+    type Err = error::Error;
+
+    /// Parse dark, light or high-contrast
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::Theme;
+    /// assert_eq!("high-contrast".parse::<Theme>().unwrap(), Theme::HighContrast);
+    /// assert!("grey".parse::<Theme>().is_err());
+    /// ```
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        from_name(s)
+    }
+}
+
+impl FromStr for Align {
+    type Err = error::Error;
+
+    /// Parse left, center or right
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::Align;
+    /// assert_eq!("center".parse::<Align>().unwrap(), Align::Center);
+    /// ```
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        from_name(s)
+    }
+}
+
 impl TryFrom<(&str, Uuid)> for Message {
     type Error = Error;
     fn try_from(received: (&str, Uuid)) -> Result<Self> {
@@ -128,7 +257,12 @@ impl TryFrom<(&str, Uuid)> for Message {
         let s = received.0.to_owned();
 
         if uuid == CHARACTERISTIC_TEXT_UUID {
-            return Ok(Message::Part(s));
+            // Only a json object opts in to chords, any other value is plain text
+            let part = s
+                .starts_with('{')
+                .then(|| serde_json::from_str::<Part>(&s).ok())
+                .flatten();
+            return Ok(Message::Part(part.unwrap_or_else(|| s.into())));
         }
 
         if uuid == CHARACTERISTIC_STATUS_UUID {
@@ -139,6 +273,12 @@ impl TryFrom<(&str, Uuid)> for Message {
             return s.parse::<Command>().map(Message::Command);
         }
 
+        if uuid == CHARACTERISTIC_PLAYLIST_UUID {
+            return serde_json::from_str::<Playlist>(&s)
+                .map(Message::Playlist)
+                .map_err(|error| Error::GattCharaceristicValueParsing(error.to_string()));
+        }
+
         Err(Error::GattCharaceristicValueParsing(s))
     }
 }
@@ -152,6 +292,16 @@ pub struct LiplScreen {
     pub dark: bool,
     #[serde(rename = "fontSize")]
     pub font_size: f32,
+    #[serde(rename = "highContrast")]
+    pub high_contrast: bool,
+    pub align: Align,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chords: Option<Vec<ChordLine>>,
+    /// Font size is chosen by the frontend so that the text fits
+    #[serde(rename = "autoFit")]
+    pub auto_fit: bool,
+    #[serde(skip)]
+    pager: Pager,
 }
 
 impl LiplScreen {
@@ -172,6 +322,59 @@ impl LiplScreen {
             ..Default::default()
         }
     }
+
+    /// Json serialization as served by the screen characteristic
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::LiplScreen;
+    /// let screen = LiplScreen::new(true, 30.0);
+    /// assert_eq!(
+    ///     screen.to_json().unwrap(),
+    ///     r#"{"text":"","status":"","dark":true,"fontSize":30.0,"highContrast":false,"align":"left","autoFit":false}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|_| Error::JsonSerialization)
+    }
+
+    /// Messages that bring a new screen into the state of this screen
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::{Command, HandleMessage, LiplScreen, Message};
+    /// let mut screen = LiplScreen::new(false, 30.0);
+    /// screen.handle_message(Message::Part("Amazing grace".into()));
+    /// let mut copy = LiplScreen::default();
+    /// screen.snapshot().into_iter().for_each(|message| copy.handle_message(message));
+    /// assert_eq!(copy.to_json().unwrap(), screen.to_json().unwrap());
+    /// ```
+    pub fn snapshot(&self) -> Vec<Message> {
+        vec![
+            Message::Command(Command::Theme(self.theme())),
+            Message::Command(Command::Align(self.align)),
+            Message::Command(Command::SetFontSize(self.font_size.round() as u32)),
+            Message::Command(Command::AutoFit(self.auto_fit)),
+            Message::Part(Part {
+                text: self.text.clone(),
+                chords: self.chords.clone(),
+            }),
+            Message::Status(self.status.clone()),
+        ]
+    }
+
+    /// Theme that the dark and high contrast settings amount to
+    pub fn theme(&self) -> Theme {
+        if self.high_contrast {
+            Theme::HighContrast
+        } else if self.dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
 }
 
 impl HandleMessage for LiplScreen {
@@ -188,40 +391,73 @@ impl HandleMessage for LiplScreen {
     //! ```
     //!
     fn handle_message(&mut self, message: Message) {
+        for message in self.pager.resolve(message) {
+            self.apply(message);
+        }
+    }
+}
+
+impl LiplScreen {
+    fn apply(&mut self, message: Message) {
         match message {
             Message::Command(command) => match command {
                 Command::Dark => {
                     self.dark = true;
+                    self.high_contrast = false;
                 }
                 Command::Light => {
                     self.dark = false;
+                    self.high_contrast = false;
                 }
                 Command::Decrease => {
+                    self.auto_fit = false;
                     self.font_size = (self.font_size - 1.0).max(2.0);
                 }
                 Command::Increase => {
+                    self.auto_fit = false;
                     self.font_size = (self.font_size + 1.0).min(100.0);
                 }
                 Command::Wait => {
                     self.text = String::new();
+                    self.chords = None;
                     WAIT_MESSAGE.clone_into(&mut self.status);
                 }
+                Command::SetFontSize(font_size) => {
+                    self.auto_fit = false;
+                    self.font_size = (font_size as f32).clamp(2.0, 100.0);
+                }
+                Command::AutoFit(auto_fit) => {
+                    self.auto_fit = auto_fit;
+                }
+                Command::Theme(theme) => {
+                    self.dark = theme != Theme::Light;
+                    self.high_contrast = theme == Theme::HighContrast;
+                }
+                Command::Align(align) => {
+                    self.align = align;
+                }
                 Command::Exit => {}
                 Command::Poweroff => {}
+                Command::Next | Command::Previous | Command::Goto(_) => {}
             },
             Message::Part(part) => {
-                self.text = part;
+                self.text = part.text;
+                self.chords = part.chords;
             }
             Message::Status(status) => {
                 self.status = status;
             }
+            Message::Playlist(_) => {}
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Command, MESSAGES};
+    use super::{
+        Align, CHARACTERISTIC_PLAYLIST_UUID, CHARACTERISTIC_TEXT_UUID, Command, HandleMessage,
+        LiplScreen, MESSAGES, Message, Part, Playlist, Theme,
+    };
 
     #[test]
     fn parse() {
@@ -233,4 +469,127 @@ mod test {
             assert_eq!(message.1.to_string(), message.0.to_string());
         }
     }
+
+    #[test]
+    fn parse_json() {
+        for command in [
+            Command::SetFontSize(48),
+            Command::Theme(Theme::HighContrast),
+            Command::Align(Align::Right),
+            Command::Goto(3),
+            Command::AutoFit(true),
+        ] {
+            assert_eq!(command.to_string().parse::<Command>().unwrap(), command);
+        }
+        assert_eq!(
+            Command::SetFontSize(48).to_string(),
+            r#"{"setFontSize":48}"#
+        );
+
+        for s in [
+            r#"{"setFontSize": -1}"#,
+            r#"{"theme": "blue"}"#,
+            r#"{"dark": true}"#,
+            "{",
+        ] {
+            assert!(s.parse::<Command>().is_err());
+        }
+    }
+
+    #[test]
+    fn absolute_values() {
+        let mut screen = LiplScreen::new(false, 30.0);
+        for (command, font_size) in [(48, 48.0), (1000, 100.0), (0, 2.0)] {
+            screen.handle_message(Message::Command(Command::SetFontSize(command)));
+            assert_eq!(screen.font_size, font_size);
+        }
+
+        screen.handle_message(Message::Command(Command::Theme(Theme::HighContrast)));
+        assert!(screen.dark && screen.high_contrast);
+        screen.handle_message(Message::Command(Command::Light));
+        assert!(!screen.dark && !screen.high_contrast);
+
+        screen.handle_message(Message::Command(Command::Align(Align::Center)));
+        assert_eq!(screen.align, Align::Center);
+
+        screen.handle_message(Message::Command(Command::AutoFit(true)));
+        assert!(screen.auto_fit);
+        screen.handle_message(Message::Command(Command::Increase));
+        assert!(!screen.auto_fit);
+    }
+
+    #[test]
+    fn playlist() {
+        let mut screen = LiplScreen::new(false, 30.0);
+        let playlist = Playlist {
+            parts: vec!["Couplet 1".to_owned(), "Refrein".to_owned()],
+            ..Default::default()
+        };
+        screen.handle_message(Message::Playlist(playlist));
+        assert_eq!(
+            (screen.text.as_str(), screen.status.as_str()),
+            ("Couplet 1", "1/2")
+        );
+        screen.handle_message(Message::Command(Command::Next));
+        assert_eq!(
+            (screen.text.as_str(), screen.status.as_str()),
+            ("Refrein", "2/2")
+        );
+        screen.handle_message(Message::Command(Command::Next));
+        assert_eq!(screen.text, "Refrein");
+        screen.handle_message(Message::Command(Command::Goto(0)));
+        assert_eq!(
+            (screen.text.as_str(), screen.status.as_str()),
+            ("Couplet 1", "1/2")
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut screen = LiplScreen::new(true, 40.0);
+        for message in [
+            Message::Command(Command::Theme(Theme::HighContrast)),
+            Message::Command(Command::Align(Align::Right)),
+            Message::Command(Command::AutoFit(true)),
+            Message::Part(Part::chordpro("[G]Amazing [C]grace")),
+            Message::Status("1/3".to_owned()),
+        ] {
+            screen.handle_message(message);
+        }
+        let mut copy = LiplScreen::new(false, 20.0);
+        for message in screen.snapshot() {
+            copy.handle_message(message);
+        }
+        assert_eq!(copy.to_json().unwrap(), screen.to_json().unwrap());
+    }
+
+    #[test]
+    fn chords() {
+        let message = Message::try_from((
+            r#"{"text":"[G]Amazing [C]grace","chords":true}"#,
+            CHARACTERISTIC_TEXT_UUID,
+        ))
+        .unwrap();
+        let mut screen = LiplScreen::new(false, 30.0);
+        screen.handle_message(message);
+        assert_eq!(screen.text, "Amazing grace");
+        assert_eq!(
+            screen.chords.as_ref().map(|lines| lines[0].chord_row()),
+            Some("G       C".to_owned())
+        );
+        let message = Message::try_from(("Refrein [2x]", CHARACTERISTIC_TEXT_UUID)).unwrap();
+        screen.handle_message(message);
+        assert_eq!(screen.text, "Refrein [2x]");
+        assert!(screen.chords.is_none());
+    }
+
+    #[test]
+    fn playlist_characteristic() {
+        let message = Message::try_from((
+            r#"{"title":"Psalm 23","parts":["De Heer is mijn herder"]}"#,
+            CHARACTERISTIC_PLAYLIST_UUID,
+        ))
+        .unwrap();
+        assert!(matches!(message, Message::Playlist(playlist) if playlist.parts.len() == 1));
+    }
 }
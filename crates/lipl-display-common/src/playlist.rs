@@ -0,0 +1,154 @@
+use crate::{Command, Message};
+use serde::{Deserialize, Serialize};
+
+/// Ordered parts of a song or setlist, uploaded at once
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Playlist {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub parts: Vec<String>,
+}
+
+/// Resolves playlist navigation locally
+///
+/// A playlist and the next, previous and goto commands are turned into the part
+/// and status to show. The status holds the page indicator, preceded by the title if any.
+///
+/// # Example
+///
+/// ```
+/// use lipl_display_common::{Command, Message, Pager, Playlist};
+/// let mut pager = Pager::default();
+/// let playlist = Playlist {
+///     parts: vec!["Couplet 1".to_owned(), "Refrein".to_owned()],
+///     ..Default::default()
+/// };
+/// pager.resolve(Message::Playlist(playlist));
+/// assert_eq!(
+///     pager.resolve(Message::Command(Command::Next)),
+///     vec![
+///         Message::Part("Refrein".into()),
+///         Message::Status("2/2".to_owned())
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Pager {
+    playlist: Option<Playlist>,
+    page: usize,
+}
+
+impl Pager {
+    /// Messages to handle instead of message
+    pub fn resolve(&mut self, message: Message) -> Vec<Message> {
+        match message {
+            Message::Playlist(playlist) => {
+                self.playlist = Some(playlist);
+                self.page = 0;
+                self.current()
+            }
+            Message::Command(Command::Next) => self.goto(self.page + 1),
+            Message::Command(Command::Previous) => match self.page.checked_sub(1) {
+                Some(page) => self.goto(page),
+                None => vec![],
+            },
+            Message::Command(Command::Goto(index)) => self.goto(index),
+            Message::Part(_) | Message::Command(Command::Wait) => {
+                self.playlist = None;
+                vec![message]
+            }
+            message => vec![message],
+        }
+    }
+
+    fn goto(&mut self, page: usize) -> Vec<Message> {
+        match &self.playlist {
+            Some(playlist) if page < playlist.parts.len() => {
+                self.page = page;
+                self.current()
+            }
+            _ => vec![],
+        }
+    }
+
+    fn current(&self) -> Vec<Message> {
+        let Some(playlist) = &self.playlist else {
+            return vec![];
+        };
+        let indicator = match playlist.parts.len() {
+            0 => String::new(),
+            count => format!("{}/{}", self.page + 1, count),
+        };
+        vec![
+            Message::Part(
+                playlist
+                    .parts
+                    .get(self.page)
+                    .map(String::as_str)
+                    .unwrap_or_default()
+                    .into(),
+            ),
+            Message::Status(match &playlist.title {
+                Some(title) => format!("{title} {indicator}").trim_end().to_owned(),
+                None => indicator,
+            }),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Pager, Playlist};
+    use crate::{Command, Message};
+
+    fn playlist() -> Playlist {
+        Playlist {
+            id: Some("1".to_owned()),
+            title: Some("Amazing Grace".to_owned()),
+            parts: (1..=7).map(|i| format!("Couplet {i}")).collect(),
+        }
+    }
+
+    fn page(part: usize) -> Vec<Message> {
+        vec![
+            Message::Part(format!("Couplet {part}").into()),
+            Message::Status(format!("Amazing Grace {part}/7")),
+        ]
+    }
+
+    #[test]
+    fn navigate() {
+        let mut pager = Pager::default();
+        assert_eq!(pager.resolve(Message::Playlist(playlist())), page(1));
+        assert!(
+            pager
+                .resolve(Message::Command(Command::Previous))
+                .is_empty()
+        );
+        assert_eq!(pager.resolve(Message::Command(Command::Next)), page(2));
+        assert_eq!(pager.resolve(Message::Command(Command::Goto(6))), page(7));
+        assert!(pager.resolve(Message::Command(Command::Next)).is_empty());
+        assert!(pager.resolve(Message::Command(Command::Goto(7))).is_empty());
+        assert_eq!(pager.resolve(Message::Command(Command::Previous)), page(6));
+    }
+
+    #[test]
+    fn part_ends_playlist() {
+        let mut pager = Pager::default();
+        pager.resolve(Message::Playlist(playlist()));
+        let part = Message::Part("Psalm 23".into());
+        assert_eq!(pager.resolve(part.clone()), vec![part]);
+        assert!(pager.resolve(Message::Command(Command::Next)).is_empty());
+        let status = Message::Status("1/1".to_owned());
+        assert_eq!(pager.resolve(status.clone()), vec![status]);
+    }
+
+    #[test]
+    fn json() {
+        let playlist = serde_json::from_str::<Playlist>(r#"{"parts":["a","b"]}"#).unwrap();
+        assert_eq!(playlist.parts.len(), 2);
+        assert!(playlist.title.is_none());
+    }
+}
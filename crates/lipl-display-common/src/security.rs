@@ -0,0 +1,73 @@
+use uuid::Uuid;
+
+use crate::CHARACTERISTIC_COMMAND_UUID;
+
+/// Security a characteristic requires from the link before accepting reads or writes
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Security {
+    /// Any connected device
+    #[default]
+    Open,
+    /// Encrypted link, the device has to pair first
+    Encrypted,
+    /// Encrypted link with an authenticated key, the device has to bond with confirmation of a passkey
+    Authenticated,
+}
+
+impl Security {
+    /// Security used for the characteristic with uuid, only commands need an authenticated link
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::{CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_TEXT_UUID, Security};
+    /// assert_eq!(Security::default_for(CHARACTERISTIC_COMMAND_UUID), Security::Authenticated);
+    /// assert_eq!(Security::default_for(CHARACTERISTIC_TEXT_UUID), Security::Open);
+    /// ```
+    pub fn default_for(uuid: Uuid) -> Self {
+        if uuid == CHARACTERISTIC_COMMAND_UUID {
+            Self::Authenticated
+        } else {
+            Self::Open
+        }
+    }
+
+    /// Bluez characteristic flag added to `read`
+    pub fn read_flag(self) -> Option<&'static str> {
+        match self {
+            Self::Open => None,
+            Self::Encrypted => Some("encrypt-read"),
+            Self::Authenticated => Some("encrypt-authenticated-read"),
+        }
+    }
+
+    /// Bluez characteristic flag added to `write` and `write-without-response`
+    pub fn write_flag(self) -> Option<&'static str> {
+        match self {
+            Self::Open => None,
+            Self::Encrypted => Some("encrypt-write"),
+            Self::Authenticated => Some("encrypt-authenticated-write"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Security;
+
+    #[test]
+    fn flags() {
+        assert_eq!(Security::Open.read_flag(), None);
+        assert_eq!(Security::Open.write_flag(), None);
+        assert_eq!(Security::Encrypted.read_flag(), Some("encrypt-read"));
+        assert_eq!(Security::Encrypted.write_flag(), Some("encrypt-write"));
+        assert_eq!(
+            Security::Authenticated.read_flag(),
+            Some("encrypt-authenticated-read")
+        );
+        assert_eq!(
+            Security::Authenticated.write_flag(),
+            Some("encrypt-authenticated-write")
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Signed envelope for values written to the display service
+//!
+//! With a secret in the access table of [`crate::access::CONFIG_FILE`], commands have to be written as
+//!
+//! ```json
+//! {"counter":42,"value":"o","hmac":"<hex>"}
+//! ```
+//!
+//! where hmac is the HMAC-SHA256 with the secret over `<counter>:<characteristic uuid>:<value>`,
+//! as `42:da35e0b2-7864-49e5-aa47-8050d1cc1484:o`.
+//! The counter has to be higher than in every envelope accepted before, also across restarts,
+//! so a recorded write cannot be replayed.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::{Error, Result};
+
+/// File with the highest counter accepted
+pub const COUNTER_FILE: &str = "/var/lib/lipl/counter";
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Envelope {
+    pub counter: u64,
+    /// Value as written to the characteristic without signing
+    pub value: String,
+    /// Lowercase hex encoded HMAC-SHA256
+    pub hmac: String,
+}
+
+fn mac(secret: &[u8], uuid: Uuid, counter: u64, value: &str) -> HmacSha256 {
+    // Hmac accepts keys of any length
+    let mut mac = HmacSha256::new_from_slice(secret).expect("Key of any length");
+    mac.update(format!("{counter}:{uuid}:{value}").as_bytes());
+    mac
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hex_decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+impl Envelope {
+    /// Sign value for the characteristic with uuid
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::{CHARACTERISTIC_COMMAND_UUID, signed::Envelope};
+    /// let envelope = Envelope::sign(b"secret", CHARACTERISTIC_COMMAND_UUID, 1, "o");
+    /// assert!(envelope.verify(b"secret", CHARACTERISTIC_COMMAND_UUID).is_ok());
+    /// assert!(envelope.verify(b"other secret", CHARACTERISTIC_COMMAND_UUID).is_err());
+    /// ```
+    pub fn sign(secret: &[u8], uuid: Uuid, counter: u64, value: impl Into<String>) -> Self {
+        let value = value.into();
+        let hmac = hex_encode(&mac(secret, uuid, counter, &value).finalize().into_bytes());
+        Self {
+            counter,
+            value,
+            hmac,
+        }
+    }
+
+    /// Check that the envelope was signed with secret for the characteristic with uuid
+    pub fn verify(&self, secret: &[u8], uuid: Uuid) -> Result<()> {
+        let hmac = hex_decode(&self.hmac).ok_or(Error::Signature)?;
+        mac(secret, uuid, self.counter, &self.value)
+            .verify_slice(&hmac)
+            .map_err(|_| Error::Signature)
+    }
+}
+
+/// Highest counter accepted, read from and written to a file if given
+#[derive(Debug, Default)]
+pub struct Counter {
+    last: Option<u64>,
+    path: Option<PathBuf>,
+}
+
+impl Counter {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { last: None, path }
+    }
+
+    fn last(&mut self) -> Result<u64> {
+        if let Some(last) = self.last {
+            return Ok(last);
+        }
+        let last = match &self.path {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(s) => s
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::Config(format!("Invalid counter in {}", path.display())))?,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => 0,
+                Err(error) => return Err(error.into()),
+            },
+            None => 0,
+        };
+        Ok(*self.last.insert(last))
+    }
+
+    /// Accept counter if higher than every counter accepted before
+    ///
+    /// The counter is stored before it is accepted, so a write that cannot be stored is rejected.
+    pub fn advance(&mut self, counter: u64) -> Result<()> {
+        if counter <= self.last()? {
+            return Err(Error::Replayed(counter));
+        }
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, counter.to_string())?;
+        }
+        self.last = Some(counter);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Counter, Envelope, HmacSha256, hex_encode};
+    use crate::{CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_STATUS_UUID, Error};
+    use hmac::Mac;
+
+    const SECRET: &[u8] = b"secret";
+
+    #[test]
+    fn hmac_sha256() {
+        // Test case 2 of RFC 4231
+        let mut mac = HmacSha256::new_from_slice(b"Jefe").unwrap();
+        mac.update(b"what do ya want for nothing?");
+        assert_eq!(
+            hex_encode(&mac.finalize().into_bytes()),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+
+        let mut mac = HmacSha256::new_from_slice(SECRET).unwrap();
+        mac.update(b"1:da35e0b2-7864-49e5-aa47-8050d1cc1484:o");
+        assert_eq!(
+            Envelope::sign(SECRET, CHARACTERISTIC_COMMAND_UUID, 1, "o").hmac,
+            hex_encode(&mac.finalize().into_bytes())
+        );
+    }
+
+    #[test]
+    fn envelope() {
+        let envelope = Envelope::sign(SECRET, CHARACTERISTIC_COMMAND_UUID, 7, "o");
+        assert_eq!(envelope.hmac.len(), 64);
+        assert!(envelope.verify(SECRET, CHARACTERISTIC_COMMAND_UUID).is_ok());
+        assert!(matches!(
+            envelope.verify(SECRET, CHARACTERISTIC_STATUS_UUID),
+            Err(Error::Signature)
+        ));
+
+        let tampered = Envelope {
+            value: "e".into(),
+            ..envelope.clone()
+        };
+        assert!(
+            tampered
+                .verify(SECRET, CHARACTERISTIC_COMMAND_UUID)
+                .is_err()
+        );
+        let garbage = Envelope {
+            hmac: "not hex".into(),
+            ..envelope
+        };
+        assert!(garbage.verify(SECRET, CHARACTERISTIC_COMMAND_UUID).is_err());
+    }
+
+    #[test]
+    fn counter() {
+        let path = std::env::temp_dir().join(format!("lipl-counter-{}", std::process::id()));
+        let mut counter = Counter::new(Some(path.clone()));
+        counter.advance(1).unwrap();
+        counter.advance(5).unwrap();
+        assert!(matches!(counter.advance(5), Err(Error::Replayed(5))));
+        assert!(matches!(counter.advance(3), Err(Error::Replayed(3))));
+
+        // Restarting does not allow replays
+        let mut counter = Counter::new(Some(path.clone()));
+        assert!(counter.advance(5).is_err());
+        counter.advance(6).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
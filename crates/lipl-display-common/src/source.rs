@@ -0,0 +1,44 @@
+use crate::Message;
+use futures_util::{FutureExt, Stream, future::BoxFuture};
+
+/// Sender for messages a source handles as if it received them itself
+pub type Injector = futures_channel::mpsc::Sender<Message>;
+
+/// Stream of messages for a display together with its lifecycle
+///
+/// Implemented by the gatt peripherals and by the json lines inputs,
+/// so that a frontend does not need to know where the messages come from.
+pub trait MessageSource: Stream<Item = Message> + Send + Unpin {
+    /// Name of the source used in logging
+    fn name(&self) -> &str;
+
+    /// Stop receiving messages and release the resources held by the source
+    fn stop(&mut self) -> BoxFuture<'_, ()> {
+        futures_util::future::ready(()).boxed()
+    }
+
+    /// Sender for messages from other controllers, for sources that keep a screen of their own
+    ///
+    /// The gatt peripherals update the screen characteristic with injected messages
+    /// before passing them on. None if the source has no state to update.
+    fn injector(&self) -> Option<Injector> {
+        None
+    }
+}
+
+/// Message source that can be selected at runtime
+pub type BoxMessageSource = Box<dyn MessageSource>;
+
+impl<S: MessageSource + ?Sized> MessageSource for Box<S> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, ()> {
+        (**self).stop()
+    }
+
+    fn injector(&self) -> Option<Injector> {
+        (**self).injector()
+    }
+}
@@ -0,0 +1,232 @@
+//! Checks on messages received by the display service, before they are handled
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{Error, Message, Part, Playlist, Result};
+
+/// Longest part accepted, in characters, also for the parts of a playlist
+pub const MAX_PART_LENGTH: usize = 8 * 1024;
+/// Longest status accepted, in characters, also for the title of a playlist
+pub const MAX_STATUS_LENGTH: usize = 256;
+/// Messages a device may send each second
+pub const DEFAULT_RATE: f64 = 10.0;
+/// Messages a device may send at once
+pub const DEFAULT_BURST: u32 = 20;
+
+/// Message with text, status and playlist without control characters other than newline, normalized to NFC
+///
+/// Commands are passed unchanged.
+///
+/// # Example
+///
+/// ```
+/// use lipl_display_common::{Message, validate::validate};
+/// assert_eq!(
+///     validate(Message::Status("1/3\u{7}".to_owned())).unwrap(),
+///     Message::Status("1/3".to_owned())
+/// );
+/// ```
+pub fn validate(message: Message) -> Result<Message> {
+    match message {
+        Message::Part(part) => clean(&part.to_chordpro(), MAX_PART_LENGTH).map(|text| {
+            Message::Part(match part.chords {
+                Some(_) => Part::chordpro(&text),
+                None => text.into(),
+            })
+        }),
+        Message::Status(status) => clean(&status, MAX_STATUS_LENGTH).map(Message::Status),
+        Message::Playlist(playlist) => Ok(Message::Playlist(Playlist {
+            title: playlist
+                .title
+                .map(|title| clean(&title, MAX_STATUS_LENGTH))
+                .transpose()?,
+            parts: playlist
+                .parts
+                .iter()
+                .map(|part| clean(part, MAX_PART_LENGTH))
+                .collect::<Result<_>>()?,
+            ..playlist
+        })),
+        Message::Command(_) => Ok(message),
+    }
+}
+
+fn clean(value: &str, max: usize) -> Result<String> {
+    let value = value
+        .chars()
+        .filter_map(|c| match c {
+            '\t' => Some(' '),
+            '\n' => Some(c),
+            c if c.is_control() => None,
+            c => Some(c),
+        })
+        .nfc()
+        .collect::<String>();
+    let length = value.chars().count();
+    if length > max {
+        return Err(Error::TooLong { length, max });
+    }
+    Ok(value)
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket for each device, refilled at rate up to burst
+#[derive(Debug)]
+pub struct RateLimit {
+    rate: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimit {
+    pub fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            burst: burst.into(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token from the bucket of device
+    pub fn check(&self, device: &str) -> Result<()> {
+        self.check_at(device, Instant::now())
+    }
+
+    fn check_at(&self, device: &str, now: Instant) -> Result<()> {
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        // Buckets refilled to burst are the same as new ones
+        let full = Duration::try_from_secs_f64(self.burst / self.rate).unwrap_or(Duration::MAX);
+        buckets.retain(|_, bucket| now.duration_since(bucket.updated) < full);
+
+        let bucket = buckets.entry(device.to_owned()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        bucket.tokens = (bucket.tokens
+            + now.duration_since(bucket.updated).as_secs_f64() * self.rate)
+            .min(self.burst);
+        bucket.updated = now;
+        if bucket.tokens < 1.0 {
+            return Err(Error::RateLimited);
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_RATE, DEFAULT_BURST)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MAX_PART_LENGTH, MAX_STATUS_LENGTH, RateLimit, validate};
+    use crate::{Command, Error, Message, Part, Playlist};
+    use std::time::{Duration, Instant};
+
+    fn status(status: &str) -> Message {
+        Message::Status(status.to_owned())
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(
+            validate(Message::Part(
+                "Amazing\tgrace\r\nhow\u{1b}[31m sweet\u{0}".into()
+            ))
+            .unwrap(),
+            Message::Part("Amazing grace\nhow[31m sweet".into())
+        );
+        assert_eq!(
+            validate(Message::Part(Part::chordpro("[G]Amazing\u{7} grace"))).unwrap(),
+            Message::Part(Part::chordpro("[G]Amazing grace"))
+        );
+        assert_eq!(
+            validate(Message::Command(Command::Dark)).unwrap(),
+            Message::Command(Command::Dark)
+        );
+    }
+
+    #[test]
+    fn nfc() {
+        assert_eq!(
+            validate(Message::Part("Cafe\u{301}".into())).unwrap(),
+            Message::Part("Caf\u{e9}".into())
+        );
+    }
+
+    #[test]
+    fn too_long() {
+        let long = "x".repeat(MAX_STATUS_LENGTH);
+        assert!(validate(status(&long)).is_ok());
+        assert!(matches!(
+            validate(status(&format!("{long}x"))),
+            Err(Error::TooLong {
+                length: 257,
+                max: 256
+            })
+        ));
+        // Control characters do not count
+        assert!(validate(status(&format!("{long}\u{7}"))).is_ok());
+    }
+
+    #[test]
+    fn playlist() {
+        let playlist = |title: &str, part: &str| {
+            Message::Playlist(Playlist {
+                title: Some(title.to_owned()),
+                parts: vec!["Couplet 1".to_owned(), part.to_owned()],
+                ..Default::default()
+            })
+        };
+        assert_eq!(
+            validate(playlist("Psalm\u{1b} 23", "Refrein\u{0}")).unwrap(),
+            playlist("Psalm 23", "Refrein")
+        );
+        assert!(matches!(
+            validate(playlist(&"x".repeat(MAX_STATUS_LENGTH + 1), "Refrein")),
+            Err(Error::TooLong { .. })
+        ));
+        assert!(matches!(
+            validate(playlist("Psalm 23", &"x".repeat(MAX_PART_LENGTH + 1))),
+            Err(Error::TooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn rate_limit() {
+        let rate_limit = RateLimit::new(2.0, 3);
+        let start = Instant::now();
+        for _ in 0..3 {
+            rate_limit.check_at("phone", start).unwrap();
+        }
+        assert!(matches!(
+            rate_limit.check_at("phone", start),
+            Err(Error::RateLimited)
+        ));
+        rate_limit.check_at("tablet", start).unwrap();
+
+        let later = start + Duration::from_millis(500);
+        rate_limit.check_at("phone", later).unwrap();
+        assert!(rate_limit.check_at("phone", later).is_err());
+
+        let idle = later + Duration::from_secs(10);
+        for _ in 0..3 {
+            rate_limit.check_at("phone", idle).unwrap();
+        }
+        assert!(rate_limit.check_at("phone", idle).is_err());
+    }
+}
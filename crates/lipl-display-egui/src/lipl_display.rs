@@ -1,58 +1,69 @@
 use std::sync::mpsc::Receiver;
 
-use eframe::egui::{Direction, Label, Layout, RichText, TextStyle};
+use eframe::egui::{Align2, Color32, FontId, Painter, Ui, vec2};
 
-use lipl_display_common::Message;
+use lipl_display_common::{
+    LiplScreen, Message,
+    layout::{Layout, Measure, Span, Viewport},
+};
 
 pub const FONT_SIZE: f32 = 40.;
 
 pub struct LiplDisplay {
-    pub text: Option<String>,
-    pub status: Option<String>,
-    pub config: LiplDisplayConfig,
+    pub screen: LiplScreen,
     pub receiver: Receiver<Message>,
 }
 
-pub struct LiplDisplayConfig {
-    pub font_size: f32,
-    pub dark: bool,
-}
+/// Text measurement with the fonts of the painter
+struct PainterMeasure<'a>(&'a Painter);
 
-impl Default for LiplDisplayConfig {
-    fn default() -> Self {
-        LiplDisplayConfig {
-            font_size: FONT_SIZE,
-            dark: true,
-        }
+impl Measure for PainterMeasure<'_> {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        self.0
+            .layout_no_wrap(
+                text.to_owned(),
+                FontId::proportional(font_size),
+                Color32::PLACEHOLDER,
+            )
+            .size()
+            .x
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        self.0
+            .layout_no_wrap(
+                String::new(),
+                FontId::proportional(font_size),
+                Color32::PLACEHOLDER,
+            )
+            .size()
+            .y
     }
 }
 
 impl LiplDisplay {
-    pub fn render_text(&self, ui: &mut eframe::egui::Ui) {
-        ui.with_layout(
-            Layout::centered_and_justified(Direction::LeftToRight),
-            |ui| {
-                if let Some(text) = &self.text {
-                    let label = Label::new(RichText::new(text).text_style(TextStyle::Body));
-                    // let label = Label::new(text).text_style(TextStyle::Body);
-                    ui.add(label);
-                }
-            },
+    pub fn render(&self, ui: &mut Ui) {
+        let rect = ui.max_rect();
+        let painter = ui.painter();
+        let color = ui.visuals().text_color();
+        let layout = Layout::new(
+            &self.screen,
+            &Viewport::new(rect.width(), rect.height()),
+            &PainterMeasure(painter),
         );
-    }
+        let draw = |span: &Span, font_size: f32| {
+            painter.text(
+                rect.min + vec2(span.x, span.y),
+                Align2::LEFT_TOP,
+                &span.text,
+                FontId::proportional(font_size),
+                color,
+            );
+        };
 
-    pub fn render_status(&self, ui: &mut eframe::egui::Ui) {
-        ui.add_space(self.config.font_size * crate::style::FONT_SMALL_FACTOR);
-        ui.with_layout(
-            Layout::centered_and_justified(Direction::LeftToRight),
-            |ui| {
-                if let Some(text) = &self.status {
-                    let label = Label::new(RichText::new(text).text_style(TextStyle::Small));
-                    // let label = Label::new(text).text_style(TextStyle::Small);
-                    ui.add(label);
-                }
-            },
-        );
-        ui.add_space(self.config.font_size * crate::style::FONT_SMALL_FACTOR);
+        for span in layout.chords.iter().chain(&layout.lines) {
+            draw(span, layout.font_size);
+        }
+        draw(&layout.status, layout.status_font_size);
     }
 }
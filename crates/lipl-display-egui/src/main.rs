@@ -1,20 +1,21 @@
 #![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
 
+mod args;
 mod fonts;
 mod lipl_display;
-mod style;
 mod visuals;
 
 use std::sync::mpsc::{Receiver, Sender};
 
+use clap::Parser;
 use eframe::{
     App, Frame, NativeOptions,
-    egui::{CentralPanel, Context, Panel, Ui, ViewportCommand},
+    egui::{CentralPanel, Context, Ui, ViewportCommand},
     run_native,
 };
 use lipl_display::LiplDisplay;
-use lipl_display_common::{BackgroundThread, Command, Message};
-use lipl_gatt_bluer::ListenBluer;
+use lipl_display_common::{Align, BackgroundThread, Command, HandleMessage, LiplScreen, Message};
+use lipl_message_source::ListenSource;
 
 const TEXT_DEFAULT: &str = "Even geduld a.u.b. ...";
 
@@ -37,27 +38,21 @@ impl LiplDisplay {
 
         cc.egui_ctx.set_fonts(fonts::fonts());
 
-        let config: lipl_display::LiplDisplayConfig = Default::default();
-
-        visuals::set_dark_mode(&cc.egui_ctx, config.dark);
-        style::set_font_size(&cc.egui_ctx, config.font_size);
+        let mut screen = LiplScreen::new(true, lipl_display::FONT_SIZE);
+        screen.align = Align::Center;
+        screen.handle_message(Message::Part(TEXT_DEFAULT.into()));
+        visuals::set_theme(&cc.egui_ctx, screen.theme());
 
         LiplDisplay {
-            text: Some(TEXT_DEFAULT.to_owned()),
-            status: None,
+            screen,
             receiver: rx,
-            config,
         }
     }
 }
 
 impl App for LiplDisplay {
     fn ui(&mut self, ui: &mut Ui, _frame: &mut Frame) {
-        Panel::bottom("Status")
-            .max_size(3. * (self.config.font_size * style::FONT_SMALL_FACTOR))
-            .show(ui, |ui| self.render_status(ui));
-
-        CentralPanel::default().show(ui, |ui| self.render_text(ui));
+        CentralPanel::default().show(ui, |ui| self.render(ui));
     }
 
     fn logic(&mut self, ctx: &Context, _frame: &mut Frame) {
@@ -65,42 +60,16 @@ impl App for LiplDisplay {
 
         if let Ok(value) = self.receiver.try_recv() {
             match value {
-                Message::Part(text) => {
-                    self.text = Some(text);
-                }
-                Message::Status(text) => {
-                    self.status = Some(text);
+                Message::Command(Command::Exit) | Message::Command(Command::Poweroff) => {
+                    ctx.send_viewport_cmd(ViewportCommand::Close);
                 }
-                Message::Command(command) => match command {
-                    Command::Dark => {
-                        self.config.dark = true;
-                        visuals::set_dark_mode(ctx, self.config.dark);
-                    }
-                    Command::Light => {
-                        self.config.dark = false;
-                        visuals::set_dark_mode(ctx, self.config.dark);
-                    }
-                    Command::Increase => {
-                        self.config.font_size += 3.0;
-                        style::set_font_size(ctx, self.config.font_size)
+                message => {
+                    let theme = self.screen.theme();
+                    self.screen.handle_message(message);
+                    if self.screen.theme() != theme {
+                        visuals::set_theme(ctx, self.screen.theme());
                     }
-                    Command::Decrease => {
-                        if self.config.font_size > 5.0 {
-                            self.config.font_size -= 3.0;
-                            style::set_font_size(ctx, self.config.font_size)
-                        };
-                    }
-                    Command::Exit => {
-                        ctx.send_viewport_cmd(ViewportCommand::Close);
-                    }
-                    Command::Poweroff => {
-                        ctx.send_viewport_cmd(ViewportCommand::Close);
-                    }
-                    Command::Wait => {
-                        self.text = Some(String::new());
-                        self.status = Some(lipl_display_common::WAIT_MESSAGE.to_owned());
-                    }
-                },
+                }
             };
         }
     }
@@ -114,10 +83,11 @@ fn fullscreen() -> NativeOptions {
 }
 
 fn main() -> anyhow::Result<()> {
+    let args = args::Args::parse();
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
 
     let (tx, rx) = std::sync::mpsc::channel::<Message>();
-    let mut gatt = ListenBluer::new(create_callback(tx));
+    let mut gatt = ListenSource::with_recording(args.source, args.record, create_callback(tx));
 
     run_native(
         "Lipl Display",
@@ -2,30 +2,30 @@ use eframe::egui::{
     Color32, Context, Stroke, Visuals,
     style::{WidgetVisuals, Widgets},
 };
+use lipl_display_common::Theme;
 
 trait ColorExt {
     fn foreground(&self) -> Color32;
     fn background(&self) -> Color32;
 }
 
-impl ColorExt for bool {
+impl ColorExt for Theme {
     fn foreground(&self) -> Color32 {
-        if *self {
-            Color32::WHITE
-        } else {
-            Color32::BLACK
+        match self {
+            Theme::Dark => Color32::WHITE,
+            Theme::Light => Color32::BLACK,
+            Theme::HighContrast => Color32::YELLOW,
         }
     }
     fn background(&self) -> Color32 {
-        if *self {
-            Color32::BLACK
-        } else {
-            Color32::WHITE
+        match self {
+            Theme::Dark | Theme::HighContrast => Color32::BLACK,
+            Theme::Light => Color32::WHITE,
         }
     }
 }
 
-fn widget_visuals(dark: bool) -> WidgetVisuals {
+fn widget_visuals(dark: Theme) -> WidgetVisuals {
     WidgetVisuals {
         bg_fill: dark.background(),
         bg_stroke: Stroke {
@@ -42,7 +42,7 @@ fn widget_visuals(dark: bool) -> WidgetVisuals {
     }
 }
 
-fn widgets(dark: bool) -> Widgets {
+fn widgets(dark: Theme) -> Widgets {
     Widgets {
         noninteractive: widget_visuals(dark),
         inactive: widget_visuals(dark),
@@ -52,15 +52,15 @@ fn widgets(dark: bool) -> Widgets {
     }
 }
 
-fn visuals(dark: bool) -> Visuals {
+fn visuals(dark: Theme) -> Visuals {
     Visuals {
-        dark_mode: dark,
+        dark_mode: dark != Theme::Light,
         widgets: widgets(dark),
         ..Default::default()
     }
 }
 
-/// if dark true then activate dark theme else light theme
-pub fn set_dark_mode(ctx: &Context, dark: bool) {
-    ctx.set_visuals(visuals(dark));
+/// activate the colors of the theme
+pub fn set_theme(ctx: &Context, theme: Theme) {
+    ctx.set_visuals(visuals(theme));
 }
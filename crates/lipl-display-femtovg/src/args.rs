@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use lipl_display_common::fit::DEFAULT_MIN_FONT_SIZE;
+use lipl_message_source::Source;
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, daemon, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
+    /// Record received messages to a file, for replay with replay:<path>
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+    /// Choose the largest font size at which a part fits the screen
+    #[arg(long)]
+    pub auto_fit: bool,
+    /// Smallest font size auto fit may choose
+    #[arg(long, default_value_t = DEFAULT_MIN_FONT_SIZE)]
+    pub min_font_size: f32,
+}
@@ -1,9 +1,13 @@
 use std::error::Error;
 
-use femtovg::{Canvas, Color, FontId, Paint, renderer::OpenGl};
+use clap::Parser;
+use femtovg::{Baseline, Canvas, Color, FontId, Paint, renderer::OpenGl};
 use glutin::surface::GlSurface;
-use lipl_display_common::{BackgroundThread, Command, HandleMessage, LiplScreen, Message};
-use lipl_gatt_bluer::ListenBluer;
+use lipl_display_common::{
+    BackgroundThread, Command, HandleMessage, LiplScreen, Message,
+    layout::{Layout, Measure, Viewport},
+};
+use lipl_message_source::ListenSource;
 use log::error;
 use winit::{
     application::ApplicationHandler,
@@ -16,13 +20,49 @@ const ROBOTO_REGULAR: &[u8] = include_bytes!("../../../font/Roboto-Regular.ttf")
 const DEFAULT_FONT_SIZE: f32 = 32.0;
 const BLACK: femtovg::Color = femtovg::Color::black();
 const WHITE: femtovg::Color = femtovg::Color::white();
+const YELLOW: femtovg::Color = femtovg::Color::rgbf(1.0, 1.0, 0.0);
 
-#[allow(dead_code)]
-mod gatt_client;
+mod args;
 mod helpers;
 
-fn get_colors(dark: bool) -> (Color, Color) {
-    if dark { (WHITE, BLACK) } else { (BLACK, WHITE) }
+fn get_colors(screen: &LiplScreen) -> (Color, Color) {
+    if screen.high_contrast {
+        (YELLOW, BLACK)
+    } else if screen.dark {
+        (WHITE, BLACK)
+    } else {
+        (BLACK, WHITE)
+    }
+}
+
+/// Text measurement with the font set on paint
+struct CanvasMeasure<'a> {
+    canvas: &'a Canvas<OpenGl>,
+    paint: &'a Paint,
+}
+
+impl CanvasMeasure<'_> {
+    fn paint(&self, font_size: f32) -> Paint {
+        let mut paint = self.paint.clone();
+        paint.set_font_size(font_size);
+        paint
+    }
+}
+
+impl Measure for CanvasMeasure<'_> {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        self.canvas
+            .measure_text(0.0, 0.0, text, &self.paint(font_size))
+            .map(|metrics| metrics.width())
+            .unwrap_or_default()
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        self.canvas
+            .measure_font(&self.paint(font_size))
+            .map(|metrics| metrics.height())
+            .unwrap_or(font_size)
+    }
 }
 
 fn create_callback(proxy: EventLoopProxy<Message>) -> impl Fn(Message) {
@@ -34,11 +74,16 @@ fn create_callback(proxy: EventLoopProxy<Message>) -> impl Fn(Message) {
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args = args::Args::parse();
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
     let event_loop = EventLoop::<Message>::with_user_event().build()?;
-    let mut gatt = ListenBluer::new(create_callback(event_loop.create_proxy()));
+    let mut gatt = ListenSource::with_recording(
+        args.source,
+        args.record,
+        create_callback(event_loop.create_proxy()),
+    );
 
-    event_loop.run_app(&mut Application::default())?;
+    event_loop.run_app(&mut Application::new(&args))?;
 
     gatt.stop();
     Ok(())
@@ -54,21 +99,22 @@ struct ApplicationGraphics {
 
 struct Application {
     screen: LiplScreen,
+    min_font_size: f32,
     graphics: Option<ApplicationGraphics>,
 }
 
-impl Default for Application {
-    fn default() -> Self {
+impl Application {
+    fn new(args: &args::Args) -> Self {
         let mut screen = LiplScreen::new(false, DEFAULT_FONT_SIZE);
-        screen.handle_message(Message::Part("Even geduld a.u.b. ..".to_owned()));
+        screen.handle_message(Message::Part("Even geduld a.u.b. ..".into()));
+        screen.auto_fit = args.auto_fit;
         Self {
             screen,
+            min_font_size: args.min_font_size,
             graphics: None,
         }
     }
-}
 
-impl Application {
     fn draw(&mut self) {
         if let Some(graphics) = self.graphics.as_mut() {
             let dpi_factor = graphics.window.scale_factor();
@@ -76,45 +122,49 @@ impl Application {
             graphics
                 .canvas
                 .set_size(size.width, size.height, dpi_factor as f32);
-            let x = 0.05 * graphics.canvas.width() as f32;
-            let mut y = 0.05 * graphics.canvas.height() as f32 + self.screen.font_size;
-
-            let (fg_color, bg_color) = get_colors(self.screen.dark);
-            graphics
-                .canvas
-                .clear_rect(0, 0, size.width, size.height, bg_color);
+            let viewport = Viewport {
+                min_font_size: self.min_font_size,
+                ..Viewport::new(
+                    graphics.canvas.width() as f32,
+                    graphics.canvas.height() as f32,
+                )
+            };
+
+            let (fg_color, bg_color) = get_colors(&self.screen);
             let mut paint = Paint::color(fg_color);
             paint.set_font(&[graphics.font_id]);
-            paint.set_font_size(self.screen.font_size);
+            paint.set_text_baseline(Baseline::Top);
+            let layout = Layout::new(
+                &self.screen,
+                &viewport,
+                &CanvasMeasure {
+                    canvas: &graphics.canvas,
+                    paint: &paint,
+                },
+            );
 
-            let font_metrics = graphics
+            graphics
                 .canvas
-                .measure_font(&paint)
-                .expect("Error measuring font");
-
-            let width = graphics.canvas.width();
+                .clear_rect(0, 0, size.width, size.height, bg_color);
 
-            let lines = graphics
-                .canvas
-                .break_text_vec(width as f32, &self.screen.text, &paint)
-                .expect("Error while breaking text");
-
-            for line_range in lines {
-                if let Ok(_res) =
-                    graphics
-                        .canvas
-                        .fill_text(x, y, &self.screen.text[line_range], &paint)
+            paint.set_font_size(layout.font_size);
+            for span in layout.chords.iter().chain(&layout.lines) {
+                if let Err(error) = graphics
+                    .canvas
+                    .fill_text(span.x, span.y, &span.text, &paint)
                 {
-                    y += font_metrics.height();
+                    error!("Cannot draw text: {error}");
                 }
             }
 
-            y = graphics.canvas.height() as f32 - font_metrics.height();
-            match graphics.canvas.fill_text(x, y, &self.screen.status, &paint) {
-                Ok(_) => {}
-                Err(e) => {
-                    eprintln!("Error: {e}");
-                }
+            paint.set_font_size(layout.status_font_size);
+            if let Err(error) = graphics.canvas.fill_text(
+                layout.status.x,
+                layout.status.y,
+                &layout.status.text,
+                &paint,
+            ) {
+                error!("Cannot draw status: {error}");
             }
 
             graphics.canvas.flush();
@@ -179,6 +229,9 @@ impl ApplicationHandler<Message> for Application {
             WindowEvent::Resized(physical_size) => {
                 log::info!("window_event: resized");
                 self.resize(physical_size);
+                if let Some(graphics) = self.graphics.as_ref() {
+                    graphics.window.request_redraw();
+                }
             }
             WindowEvent::CloseRequested => {
                 log::debug!("window_event close");
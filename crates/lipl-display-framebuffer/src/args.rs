@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use lipl_message_source::Source;
+
+use crate::framebuffer::Geometry;
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, daemon, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
+    /// Record received messages to a file, for replay with replay:<path>
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+    /// Framebuffer device or file
+    #[arg(short, long, default_value = "/dev/fb0")]
+    pub device: PathBuf,
+    /// Width and height of a fake framebuffer, as 800x480
+    #[arg(long)]
+    pub geometry: Option<Geometry>,
+    /// Pixel format of a fake framebuffer: 16 for rgb565 or 32 for xrgb8888
+    #[arg(long, default_value_t = 32)]
+    pub bits_per_pixel: u32,
+    #[arg(long, default_value_t = 32)]
+    pub font_size: u32,
+}
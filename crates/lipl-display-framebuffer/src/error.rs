@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Rendering: {0}")]
+    Render(#[from] lipl_display_headless::Error),
+
+    #[error("Invalid geometry {0}")]
+    Geometry(String),
+
+    #[error("Unsupported bits per pixel {0}")]
+    BitsPerPixel(u32),
+}
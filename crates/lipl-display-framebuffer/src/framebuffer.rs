@@ -0,0 +1,218 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use tiny_skia::Pixmap;
+
+use crate::{Error, Result};
+
+/// Visible size in pixels
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Geometry {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl FromStr for Geometry {
+    type Err = Error;
+
+    /// Parse width and height separated by x or a comma, as in sysfs
+    fn from_str(s: &str) -> Result<Self> {
+        let (width, height) = s
+            .trim()
+            .split_once(['x', ','])
+            .ok_or_else(|| Error::Geometry(s.to_owned()))?;
+        match (width.parse(), height.parse()) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => Ok(Self { width, height }),
+            _ => Err(Error::Geometry(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PixelFormat {
+    Rgb565,
+    Xrgb8888,
+}
+
+impl PixelFormat {
+    pub fn from_bits_per_pixel(bits_per_pixel: u32) -> Result<Self> {
+        match bits_per_pixel {
+            16 => Ok(Self::Rgb565),
+            32 => Ok(Self::Xrgb8888),
+            _ => Err(Error::BitsPerPixel(bits_per_pixel)),
+        }
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Rgb565 => 2,
+            Self::Xrgb8888 => 4,
+        }
+    }
+
+    /// Little endian bytes of an opaque pixel
+    fn put(&self, row: &mut Vec<u8>, red: u8, green: u8, blue: u8) {
+        match self {
+            Self::Rgb565 => {
+                let value = (u16::from(red >> 3) << 11)
+                    | (u16::from(green >> 2) << 5)
+                    | u16::from(blue >> 3);
+                row.extend_from_slice(&value.to_le_bytes());
+            }
+            Self::Xrgb8888 => row.extend_from_slice(&[blue, green, red, 0xff]),
+        }
+    }
+}
+
+/// Framebuffer device, or a regular file standing in for one
+pub struct Framebuffer {
+    file: File,
+    pub geometry: Geometry,
+    format: PixelFormat,
+    /// Bytes per row, including padding
+    stride: usize,
+}
+
+fn read_sysfs(dir: &Path, name: &str) -> Result<String> {
+    Ok(std::fs::read_to_string(dir.join(name))?.trim().to_owned())
+}
+
+impl Framebuffer {
+    /// Framebuffer device with size and pixel format from sysfs
+    pub fn open_device(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let name = path
+            .file_name()
+            .ok_or_else(|| Error::Geometry(path.display().to_string()))?;
+        let sysfs = PathBuf::from("/sys/class/graphics").join(name);
+        let geometry = read_sysfs(&sysfs, "virtual_size")?.parse::<Geometry>()?;
+        let bits_per_pixel = read_sysfs(&sysfs, "bits_per_pixel")?;
+        let format = PixelFormat::from_bits_per_pixel(
+            bits_per_pixel
+                .parse()
+                .map_err(|_| Error::Geometry(bits_per_pixel.clone()))?,
+        )?;
+        let stride = read_sysfs(&sysfs, "stride")?;
+        let stride = stride
+            .parse()
+            .map_err(|_| Error::Geometry(stride.clone()))?;
+        let file = OpenOptions::new().write(true).open(path)?;
+        Ok(Self {
+            file,
+            geometry,
+            format,
+            stride,
+        })
+    }
+
+    /// Regular file without padding, created if missing
+    pub fn open_file(
+        path: impl AsRef<Path>,
+        geometry: Geometry,
+        format: PixelFormat,
+    ) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+        Ok(Self {
+            file,
+            geometry,
+            format,
+            stride: geometry.width as usize * format.bytes_per_pixel(),
+        })
+    }
+
+    /// Copy the pixmap to the top left of the framebuffer
+    pub fn write(&mut self, pixmap: &Pixmap) -> Result<()> {
+        let width = pixmap.width().min(self.geometry.width) as usize;
+        let height = pixmap.height().min(self.geometry.height) as usize;
+        let mut row = Vec::with_capacity(width * self.format.bytes_per_pixel());
+        for (y, pixels) in pixmap
+            .pixels()
+            .chunks(pixmap.width() as usize)
+            .take(height)
+            .enumerate()
+        {
+            row.clear();
+            for pixel in &pixels[..width] {
+                self.format
+                    .put(&mut row, pixel.red(), pixel.green(), pixel.blue());
+            }
+            self.file.seek(SeekFrom::Start((y * self.stride) as u64))?;
+            self.file.write_all(&row)?;
+        }
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tiny_skia::{Color, Pixmap};
+
+    use super::{Framebuffer, Geometry, PixelFormat};
+
+    fn pixmap(width: u32, height: u32) -> Pixmap {
+        let mut pixmap = Pixmap::new(width, height).unwrap();
+        pixmap.fill(Color::from_rgba8(255, 128, 0, 255));
+        pixmap
+    }
+
+    #[test]
+    fn geometry() {
+        assert_eq!(
+            "800x480".parse::<Geometry>().unwrap(),
+            Geometry {
+                width: 800,
+                height: 480
+            }
+        );
+        assert_eq!("1920,1080\n".parse::<Geometry>().unwrap().height, 1080);
+        assert!("800".parse::<Geometry>().is_err());
+        assert!("0x480".parse::<Geometry>().is_err());
+    }
+
+    #[test]
+    fn fake_framebuffer() {
+        let path = std::env::temp_dir().join(format!("lipl-fb-{}", std::process::id()));
+        let geometry = Geometry {
+            width: 4,
+            height: 2,
+        };
+
+        let mut framebuffer =
+            Framebuffer::open_file(&path, geometry, PixelFormat::Xrgb8888).unwrap();
+        framebuffer.write(&pixmap(4, 2)).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), 4 * 2 * 4);
+        assert!(bytes.chunks(4).all(|pixel| pixel == [0, 128, 255, 255]));
+
+        let mut framebuffer = Framebuffer::open_file(&path, geometry, PixelFormat::Rgb565).unwrap();
+        framebuffer.write(&pixmap(4, 2)).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let expected = ((31u16 << 11) | (32 << 5)).to_le_bytes();
+        assert!(bytes[..16].chunks(2).all(|pixel| pixel == expected));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn larger_pixmap_is_clipped() {
+        let path = std::env::temp_dir().join(format!("lipl-fb-clip-{}", std::process::id()));
+        let geometry = Geometry {
+            width: 2,
+            height: 2,
+        };
+        let mut framebuffer =
+            Framebuffer::open_file(&path, geometry, PixelFormat::Xrgb8888).unwrap();
+        framebuffer.write(&pixmap(3, 3)).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap().len(), 2 * 2 * 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
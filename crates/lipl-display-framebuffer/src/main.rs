@@ -0,0 +1,62 @@
+#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+
+use std::sync::mpsc::Sender;
+
+use clap::Parser;
+use framebuffer::{Framebuffer, PixelFormat};
+use lipl_display_common::{BackgroundThread, Command, HandleMessage, LiplScreen, Message};
+use lipl_display_headless::Renderer;
+use lipl_message_source::ListenSource;
+
+use error::Error;
+
+mod args;
+mod error;
+mod framebuffer;
+
+type Result<T> = std::result::Result<T, Error>;
+
+fn create_callback(tx: Sender<Message>) -> impl Fn(Message) {
+    move |message| {
+        if let Err(error) = tx.send(message) {
+            log::error!("Error sending message: {error}");
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let args = args::Args::parse();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let mut framebuffer = match args.geometry {
+        Some(geometry) => Framebuffer::open_file(
+            &args.device,
+            geometry,
+            PixelFormat::from_bits_per_pixel(args.bits_per_pixel)?,
+        )?,
+        None => Framebuffer::open_device(&args.device)?,
+    };
+    let renderer = Renderer::new()?;
+    let mut screen = LiplScreen::new(true, args.font_size as f32);
+
+    let (tx, rx) = std::sync::mpsc::channel::<Message>();
+    let mut source = ListenSource::with_recording(args.source, args.record, create_callback(tx));
+
+    let geometry = framebuffer.geometry;
+    framebuffer.write(&renderer.render(&screen, geometry.width, geometry.height)?)?;
+    while let Ok(message) = rx.recv() {
+        if [
+            Message::Command(Command::Exit),
+            Message::Command(Command::Poweroff),
+        ]
+        .contains(&message)
+        {
+            break;
+        }
+        screen.handle_message(message);
+        framebuffer.write(&renderer.render(&screen, geometry.width, geometry.height)?)?;
+    }
+
+    source.stop();
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use std::sync::LazyLock;
+
+use lipl_display_common::{
+    Command, HandleMessage, LiplScreen, Message,
+    layout::{Layout, Viewport},
+};
+use lipl_display_headless::Renderer;
+
+use crate::{constant::FONT_SIZE, theme::Theme};
+
+/// Measurement with the bundled Roboto font, the font the window draws with
+static MEASURE: LazyLock<Renderer> =
+    LazyLock::new(|| Renderer::new().expect("Bundled font is valid"));
+
+#[derive(Clone)]
+pub struct Screen(LiplScreen);
+
+impl Default for Screen {
+    fn default() -> Self {
+        let mut screen = LiplScreen::new(true, FONT_SIZE);
+        screen.handle_message(Message::Command(Command::Wait));
+        Self(screen)
+    }
+}
+
+impl Screen {
+    pub fn handle_message(&mut self, message: Message) {
+        self.0.handle_message(message);
+    }
+
+    pub fn layout(&self, viewport: &Viewport) -> Layout {
+        Layout::new(&self.0, viewport, &*MEASURE)
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.0.theme().into()
+    }
+}
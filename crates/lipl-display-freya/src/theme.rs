@@ -4,28 +4,23 @@ use freya::prelude::Color;
 pub enum Theme {
     Dark,
     Light,
+    HighContrast,
 }
 
-impl From<bool> for Theme {
-    fn from(dark: bool) -> Self {
-        if dark { Theme::Dark } else { Theme::Light }
+impl From<lipl_display_common::Theme> for Theme {
+    fn from(theme: lipl_display_common::Theme) -> Self {
+        match theme {
+            lipl_display_common::Theme::Dark => Theme::Dark,
+            lipl_display_common::Theme::Light => Theme::Light,
+            lipl_display_common::Theme::HighContrast => Theme::HighContrast,
+        }
     }
 }
 
 impl Theme {
-    pub fn set(&mut self, theme: Theme) {
-        *self = theme;
-    }
-
-    pub fn dark() -> Self {
-        Theme::Dark
-    }
-    pub fn light() -> Self {
-        Theme::Light
-    }
     pub fn bg_color(&self) -> Color {
         match self {
-            Self::Dark => Color::BLACK,
+            Self::Dark | Self::HighContrast => Color::BLACK,
             Self::Light => Color::WHITE,
         }
     }
@@ -33,6 +28,7 @@ impl Theme {
         match self {
             Self::Dark => Color::WHITE,
             Self::Light => Color::BLACK,
+            Self::HighContrast => Color::YELLOW,
         }
     }
 }
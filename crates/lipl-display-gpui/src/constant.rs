@@ -1,8 +1,9 @@
 pub const DARK: bool = true;
 pub const INITIAL_FONT_SIZE: f32 = 20.0;
-pub const MIN_FONT_SIZE: usize = 5;
+pub const MIN_FONT_SIZE: f32 = 5.0;
 pub const FONT: &str = "Roboto";
-pub const DEFAULT_STATUS: &str = "Even geduld a.u.b. ...";
+/// Height of a line relative to the font size
+pub const LINE_HEIGHT: f32 = 1.2;
 pub const WINDOW_WIDTH: f32 = 500.;
 pub const WINDOW_HEIGHT: f32 = 500.;
 pub const APP_ID: &str = "nl.paulmin.lipl_display";
@@ -1,9 +1,8 @@
 use async_channel::Receiver;
-use gpui::{AppContext, AsyncApp, Entity, Hsla, Pixels, WeakEntity};
-use lipl_display_common::{Command, Message};
-use std::cmp::max;
+use gpui::{AppContext, AsyncApp, Entity, Hsla, WeakEntity};
+use lipl_display_common::{HandleMessage, Message};
 
-use crate::constant::{DARK, DEFAULT_STATUS, INITIAL_FONT_SIZE, MIN_FONT_SIZE};
+use crate::constant::{DARK, INITIAL_FONT_SIZE};
 
 fn update(
     lipl_screen_weak: &WeakEntity<LiplScreen>,
@@ -17,18 +16,6 @@ fn update(
     }
 }
 
-// fn update_no(
-//     lipl_screen_weak: &WeakEntity<LiplScreen>,
-//     cx: &mut gpui::AsyncApp,
-//     f: fn(&mut LiplScreen),
-// ) {
-//     if let Some(lipl_screen) = lipl_screen_weak.upgrade().as_ref() {
-//         cx.update_entity(lipl_screen, |screen, _| {
-//             f(screen);
-//         });
-//     }
-// }
-
 pub fn init(cx: &mut gpui::App, receiver: Receiver<Message>) -> Entity<LiplScreen> {
     let lipl_screen = cx.new(|_| LiplScreen::new(DARK, INITIAL_FONT_SIZE));
     let _ = cx.observe(&lipl_screen, |_, cx| {
@@ -37,43 +24,9 @@ pub fn init(cx: &mut gpui::App, receiver: Receiver<Message>) -> Entity<LiplScree
     let lipl_screen_weak = lipl_screen.downgrade();
     cx.spawn(async move |cx: &mut AsyncApp| {
         while let Ok(message) = receiver.recv().await {
-            match message {
-                Message::Part(part) => {
-                    update(&lipl_screen_weak, cx, |screen| screen.set_text(&part));
-                }
-                Message::Status(status) => {
-                    // Process the message
-                    update(&lipl_screen_weak, cx, |screen| screen.set_status(&status));
-                }
-                Message::Command(command) => {
-                    match command {
-                        Command::Dark => {
-                            // Process the message
-                            update(&lipl_screen_weak, cx, |screen| screen.set_dark(true));
-                        }
-                        Command::Light => {
-                            // Process the message
-                            update(&lipl_screen_weak, cx, |screen| screen.set_dark(false));
-                        }
-                        Command::Exit => {}
-                        Command::Poweroff => {
-                            // Process the message
-                        }
-                        Command::Increase => {
-                            update(&lipl_screen_weak, cx, |screen| screen.increase_font_size());
-                        }
-                        Command::Decrease => {
-                            update(&lipl_screen_weak, cx, |screen| screen.decrease_font_size());
-                        }
-                        Command::Wait => {
-                            update(&lipl_screen_weak, cx, |screen| screen.set_text(""));
-                            update(&lipl_screen_weak, cx, |screen| {
-                                screen.set_status(DEFAULT_STATUS)
-                            });
-                        }
-                    }
-                }
-            }
+            update(&lipl_screen_weak, cx, |screen| {
+                screen.handle(message.clone())
+            });
             cx.refresh();
         }
     })
@@ -81,7 +34,7 @@ pub fn init(cx: &mut gpui::App, receiver: Receiver<Message>) -> Entity<LiplScree
     lipl_screen
 }
 
-pub struct LiplScreen(lipl_display_common::LiplScreen);
+pub struct LiplScreen(pub lipl_display_common::LiplScreen);
 
 impl LiplScreen {
     pub fn new(dark: bool, initial_fontsize: f32) -> Self {
@@ -96,40 +49,15 @@ impl LiplScreen {
     }
 
     pub fn foreground_color(&self) -> Hsla {
-        if self.0.dark {
+        if self.0.high_contrast {
+            gpui::yellow()
+        } else if self.0.dark {
             Hsla::white()
         } else {
             Hsla::black()
         }
     }
-    pub fn text(&self) -> String {
-        self.0.text.clone()
-    }
-    pub fn status(&self) -> String {
-        self.0.status.clone()
-    }
-    pub fn set_text(&mut self, text: &str) {
-        self.0.text = text.into();
-    }
-    pub fn set_status(&mut self, status: &str) {
-        self.0.status = status.into();
-    }
-    pub fn set_dark(&mut self, dark: bool) {
-        self.0.dark = dark;
-    }
-    pub fn font_size(&self) -> Pixels {
-        (self.0.font_size as usize).into()
-    }
-
-    pub fn font_size_status(&self) -> Pixels {
-        (max(self.0.font_size as usize - 2, MIN_FONT_SIZE)).into()
-    }
-    pub fn increase_font_size(&mut self) {
-        self.0.font_size += 1.0;
-    }
-    pub fn decrease_font_size(&mut self) {
-        if self.0.font_size as usize > MIN_FONT_SIZE {
-            self.0.font_size -= 1.0;
-        }
+    pub fn handle(&mut self, message: Message) {
+        self.0.handle_message(message);
     }
 }
@@ -2,12 +2,12 @@ use anyhow::Error;
 use async_channel::Sender;
 use futures::StreamExt;
 use gpui::App;
-use lipl_display_common::{Command, Message};
-use lipl_gatt_bluer::listen_stream;
+use lipl_display_common::{Command, Message, MessageSource};
+use lipl_message_source::{Source, open};
 
-pub fn init(cx: &mut App, sender: Sender<Message>) {
+pub fn init(cx: &mut App, sender: Sender<Message>, source: Source) {
     gpui_tokio::Tokio::handle(cx).spawn(async move {
-        let mut s = listen_stream()
+        let mut s = open(&source)
             .await
             .inspect_err(|e| log::error!("Error:{e}"))
             .map_err(Error::from)?;
@@ -24,6 +24,7 @@ pub fn init(cx: &mut App, sender: Sender<Message>) {
                 .inspect_err(|e| log::error!("Error: {e}"))
                 .map_err(Error::from)?;
         }
+        s.stop().await;
         Ok::<(), Error>(())
     });
 }
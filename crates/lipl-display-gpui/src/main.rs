@@ -1,36 +1,89 @@
-use constant::{APP_ID, FONT, WINDOW_HEIGHT, WINDOW_WIDTH};
+use clap::Parser;
+use constant::{APP_ID, FONT, LINE_HEIGHT, MIN_FONT_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
 use gpui::{
-    App, Application, Bounds, Context, IntoElement, ParentElement, Render, Styled, Window,
-    WindowBounds, WindowOptions, div, px, size,
+    App, Application, Bounds, Context, Div, Font, Hsla, IntoElement, ParentElement, Render, Styled,
+    TextRun, Window, WindowBounds, WindowOptions, div, font, px, size,
 };
 
-use lipl_display_common::Message;
+use lipl_display_common::{
+    Message,
+    layout::{Layout, Measure, Span, Viewport},
+};
 use lipl_screen::LiplScreen;
 
+mod args;
 mod constant;
 mod lipl_screen;
-mod listen_bluer;
+mod listen_source;
+
+/// Text measurement with the text system of the window
+struct WindowMeasure<'a> {
+    window: &'a Window,
+    font: Font,
+}
+
+impl Measure for WindowMeasure<'_> {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        let run = TextRun {
+            len: text.len(),
+            font: self.font.clone(),
+            color: Hsla::default(),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+        };
+        self.window
+            .text_system()
+            .shape_line(text.to_owned().into(), px(font_size), &[run], None)
+            .width
+            .into()
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        font_size * LINE_HEIGHT
+    }
+}
+
+fn span(span: &Span, font_size: f32) -> Div {
+    div()
+        .absolute()
+        .left(px(span.x))
+        .top(px(span.y))
+        .text_size(px(font_size))
+        .line_height(px(font_size * LINE_HEIGHT))
+        .whitespace_nowrap()
+        .child(span.text.clone())
+}
 
 impl Render for LiplScreen {
     fn render(&mut self, window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        let viewport_size = window.viewport_size();
+        let viewport = Viewport {
+            min_font_size: MIN_FONT_SIZE,
+            ..Viewport::new(viewport_size.width.into(), viewport_size.height.into())
+        };
+        let layout = Layout::new(
+            &self.0,
+            &viewport,
+            &WindowMeasure {
+                window,
+                font: font(FONT),
+            },
+        );
         div()
-            .h(window.bounds().bottom())
-            .w(window.bounds().right())
+            .relative()
+            .size_full()
             .bg(self.background_color())
             .text_color(self.foreground_color())
-            .text_size(self.font_size())
             .font_family(FONT)
-            .children([
-                div()
-                    .h(0.9 * window.bounds().bottom())
-                    .child(self.text())
-                    .p(self.font_size()),
-                div()
-                    .h(0.1 * window.bounds().bottom())
-                    .child(self.status())
-                    .p(self.font_size())
-                    .text_size(self.font_size_status()),
-            ])
+            .children(
+                layout
+                    .chords
+                    .iter()
+                    .chain(&layout.lines)
+                    .map(|s| span(s, layout.font_size)),
+            )
+            .child(span(&layout.status, layout.status_font_size))
     }
 }
 
@@ -43,12 +96,13 @@ fn window_bounds(cx: &mut App) -> Option<WindowBounds> {
 }
 
 fn main() {
+    let args = args::Args::parse();
     let linux_platform = gpui_linux::current_platform(false);
     env_logger::init();
-    Application::with_platform(linux_platform).run(|cx: &mut App| {
+    Application::with_platform(linux_platform).run(move |cx: &mut App| {
         gpui_tokio::init(cx);
         let (sender, receiver) = async_channel::unbounded::<Message>();
-        listen_bluer::init(cx, sender);
+        listen_source::init(cx, sender, args.source);
         let window_bounds = window_bounds(cx);
         cx.open_window(
             WindowOptions {
@@ -0,0 +1,10 @@
+use clap::Parser;
+use lipl_message_source::Source;
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, daemon, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
+}
@@ -2,15 +2,16 @@ use std::{cell::RefCell, rc::Rc};
 
 use anyhow::Result;
 use async_channel::{Sender, bounded};
+use clap::Parser;
 use gtk4::{
     glib::clone,
     prelude::{ApplicationExt, ApplicationExtManual},
 };
 use lipl_display_common::{BackgroundThread, Command, Message};
-use lipl_gatt_bluer::ListenBluer;
+use lipl_message_source::{ListenSource, Source};
 use log::{error, trace};
 
-mod css;
+mod args;
 mod cursor;
 mod window;
 
@@ -27,14 +28,14 @@ fn create_callback(tx: Sender<Message>) -> impl Fn(Message) {
     }
 }
 
-fn build_ui(application: &gtk4::Application) -> Result<()> {
+fn build_ui(application: &gtk4::Application, source: Source) -> Result<()> {
     let (values_tx, values_rx) = bounded(1);
-    let gatt = Rc::new(RefCell::new(ListenBluer::new(create_callback(values_tx))));
+    let gatt = Rc::new(RefCell::new(ListenSource::new(
+        source,
+        create_callback(values_tx),
+    )));
 
-    css::load(css::Theme::Dark);
-
-    let mut app_window = window::AppWindow::new(application)?;
-    let window_clone = app_window.clone();
+    let app_window = window::AppWindow::new(application)?;
 
     application.connect_shutdown(clone!(
         #[strong]
@@ -45,49 +46,14 @@ fn build_ui(application: &gtk4::Application) -> Result<()> {
     ));
 
     glib::spawn_future_local(async move {
-        while let Ok(value) = values_rx.recv().await {
-            match value {
-                Message::Part(s) => {
-                    app_window.set_text(&s);
-                    trace!("Text updated");
-                }
-                Message::Status(s) => {
-                    app_window.set_status(&s);
-                    trace!("Status updated");
+        while let Ok(message) = values_rx.recv().await {
+            trace!("Message {message:?}");
+            match message {
+                Message::Command(Command::Exit | Command::Poweroff) => {
+                    app_window.close();
+                    break;
                 }
-                Message::Command(command) => match command {
-                    Command::Increase => {
-                        app_window.increase_font_size();
-                        trace!("Increase font size");
-                    }
-                    Command::Decrease => {
-                        app_window.decrease_font_size();
-                        trace!("Decrease font size");
-                    }
-                    Command::Light => {
-                        css::load(css::Theme::Light);
-                        trace!("Light theme");
-                    }
-                    Command::Dark => {
-                        css::load(css::Theme::Dark);
-                        trace!("Dark theme");
-                    }
-                    Command::Exit => {
-                        window_clone.close();
-                        trace!("Exit");
-                        break;
-                    }
-                    Command::Poweroff => {
-                        window_clone.close();
-                        trace!("Poweroff");
-                        break;
-                    }
-                    Command::Wait => {
-                        app_window.set_status(lipl_display_common::WAIT_MESSAGE);
-                        app_window.set_text("");
-                        trace!("Status Wait");
-                    }
-                },
+                message => app_window.handle_message(message),
             }
         }
     });
@@ -96,6 +62,7 @@ fn build_ui(application: &gtk4::Application) -> Result<()> {
 }
 
 fn main() -> Result<gtk4::glib::ExitCode> {
+    let args = args::Args::parse();
     log::set_logger(&GLIB_LOGGER).unwrap();
     log::set_max_level(log::LevelFilter::Trace);
 
@@ -105,10 +72,11 @@ fn main() -> Result<gtk4::glib::ExitCode> {
         .build();
 
     application.connect_activate(move |app| {
-        if let Err(err) = build_ui(app) {
+        if let Err(err) = build_ui(app, args.source.clone()) {
             eprintln!("{err}");
         }
     });
 
-    Ok(application.run())
+    // Arguments are already parsed by clap
+    Ok(application.run_with_args::<&str>(&[]))
 }
@@ -1,51 +1,119 @@
-use anyhow::{Result, anyhow};
-use gtk4::prelude::*;
+use std::{cell::RefCell, rc::Rc};
 
-pub const TEXT_ID: &str = "text";
-pub const PAGNOS_ID: &str = "pagenos";
+use anyhow::{Result, anyhow};
+use gtk4::{cairo, prelude::*};
+use lipl_display_common::{
+    HandleMessage, LiplScreen, Message, Theme,
+    layout::{Layout, Measure, Span, Viewport},
+};
+use log::error;
+
+pub const SCREEN_ID: &str = "screen";
 pub const WINDOW_ID: &str = "window";
 pub const TEXT_INIT: &str = "Even geduld a.u.b. ...";
+pub const FONT_SIZE: f32 = 40.0;
+pub const FONT_FAMILY: &str = "Sans";
 pub const WINDOW_UI: &str = include_str!("window.ui");
 
-#[derive(Clone)]
-pub struct Data {
-    pub text: String,
-    pub status: String,
-    pub font_size: u16,
+type Rgb = (f64, f64, f64);
+
+const BLACK: Rgb = (0.0, 0.0, 0.0);
+const WHITE: Rgb = (1.0, 1.0, 1.0);
+const YELLOW: Rgb = (1.0, 1.0, 0.0);
+
+fn get_colors(screen: &LiplScreen) -> (Rgb, Rgb) {
+    match screen.theme() {
+        Theme::HighContrast => (YELLOW, BLACK),
+        Theme::Dark => (WHITE, BLACK),
+        Theme::Light => (BLACK, WHITE),
+    }
 }
 
-impl Default for Data {
-    fn default() -> Self {
-        Data {
-            text: TEXT_INIT.to_owned(),
-            status: "".to_owned(),
-            font_size: 40,
+/// Text measurement with the font selected on the cairo context
+struct CairoMeasure<'a>(&'a cairo::Context);
+
+impl Measure for CairoMeasure<'_> {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        self.0.set_font_size(font_size.into());
+        self.0
+            .text_extents(text)
+            .map(|extents| extents.x_advance() as f32)
+            .unwrap_or_default()
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        self.0.set_font_size(font_size.into());
+        self.0
+            .font_extents()
+            .map(|extents| extents.height() as f32)
+            .unwrap_or(font_size)
+    }
+}
+
+fn draw(screen: &LiplScreen, context: &cairo::Context, width: i32, height: i32) {
+    let ((fg_r, fg_g, fg_b), (bg_r, bg_g, bg_b)) = get_colors(screen);
+    context.set_source_rgb(bg_r, bg_g, bg_b);
+    if let Err(error) = context.paint() {
+        error!("Cannot draw background: {error}");
+    }
+
+    context.select_font_face(
+        FONT_FAMILY,
+        cairo::FontSlant::Normal,
+        cairo::FontWeight::Normal,
+    );
+    let layout = Layout::new(
+        screen,
+        &Viewport::new(width as f32, height as f32),
+        &CairoMeasure(context),
+    );
+
+    context.set_source_rgb(fg_r, fg_g, fg_b);
+    let draw_span = |span: &Span, font_size: f32| {
+        context.set_font_size(font_size.into());
+        // Spans are positioned by their top, cairo draws text on the baseline
+        let ascent = context
+            .font_extents()
+            .map(|extents| extents.ascent())
+            .unwrap_or(font_size.into());
+        context.move_to(span.x.into(), f64::from(span.y) + ascent);
+        if let Err(error) = context.show_text(&span.text) {
+            error!("Cannot draw text: {error}");
         }
+    };
+
+    for span in layout.chords.iter().chain(&layout.lines) {
+        draw_span(span, layout.font_size);
     }
+    draw_span(&layout.status, layout.status_font_size);
 }
 
 #[derive(Clone)]
 pub struct AppWindow {
     pub window: gtk4::ApplicationWindow,
-    pub text: gtk4::Label,
-    pub status: gtk4::Label,
-    data: Data,
+    pub area: gtk4::DrawingArea,
+    screen: Rc<RefCell<LiplScreen>>,
 }
 
 impl AppWindow {
     pub fn new(application: &gtk4::Application) -> Result<Self> {
         let builder = gtk4::Builder::from_string(WINDOW_UI);
-        // let mut cancel_clone = cancel.clone();
 
         let window: gtk4::ApplicationWindow = builder
             .object(WINDOW_ID)
             .ok_or_else(|| anyhow!("Missing Window control"))?;
-        let text: gtk4::Label = builder
-            .object(TEXT_ID)
-            .ok_or_else(|| anyhow!("Missing text control"))?;
-        let status: gtk4::Label = builder
-            .object(PAGNOS_ID)
-            .ok_or_else(|| anyhow!("Missing pagnos control"))?;
+        let area: gtk4::DrawingArea = builder
+            .object(SCREEN_ID)
+            .ok_or_else(|| anyhow!("Missing screen control"))?;
+
+        let mut screen = LiplScreen::new(true, FONT_SIZE);
+        screen.handle_message(Message::Part(TEXT_INIT.into()));
+        let screen = Rc::new(RefCell::new(screen));
+
+        let draw_screen = screen.clone();
+        area.set_draw_func(move |_, context, width, height| {
+            draw(&draw_screen.borrow(), context, width, height);
+        });
 
         window.set_application(Some(application));
         window.fullscreen();
@@ -55,52 +123,16 @@ impl AppWindow {
 
         let app_window = AppWindow {
             window,
-            text,
-            status,
-            data: Default::default(),
+            area,
+            screen,
         };
 
         Ok(app_window)
     }
 
-    pub fn set_text(&mut self, text: &str) {
-        self.data.text = text.to_owned();
-        self.update_text_label();
-    }
-
-    pub fn set_status(&mut self, text: &str) {
-        self.data.status = text.to_owned();
-        self.update_status_label();
-    }
-
-    pub fn increase_font_size(&mut self) {
-        self.data.font_size += 1;
-        self.refresh();
-    }
-
-    pub fn decrease_font_size(&mut self) {
-        self.data.font_size -= 1;
-        self.refresh();
-    }
-
-    fn refresh(&self) {
-        self.update_status_label();
-        self.update_text_label();
-    }
-
-    fn update_text_label(&self) {
-        self.text.set_markup(&format!(
-            "<span font=\"{}\">{}</span>",
-            self.data.font_size, self.data.text
-        ));
-    }
-
-    fn update_status_label(&self) {
-        self.status.set_markup(&format!(
-            "<span font=\"{}\">{}</span>",
-            self.data.font_size / 2,
-            self.data.status
-        ));
+    pub fn handle_message(&self, message: Message) {
+        self.screen.borrow_mut().handle_message(message);
+        self.area.queue_draw();
     }
 
     pub fn close(&self) {
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Invalid image size {0}x{1}")]
+    Size(u32, u32),
+
+    #[error("Invalid font")]
+    Font,
+
+    #[error("Png encoding: {0}")]
+    Png(String),
+}
@@ -0,0 +1,252 @@
+#![doc = include_str!("../README.md")]
+
+use std::path::Path;
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use lipl_display_common::{
+    LiplScreen, Theme,
+    layout::{Layout, Measure, Span, Viewport},
+};
+use tiny_skia::{Color, Pixmap, PremultipliedColorU8};
+
+pub use error::Error;
+
+mod error;
+
+type Result<T> = std::result::Result<T, Error>;
+
+const ROBOTO_REGULAR: &[u8] = include_bytes!("../../../font/Roboto-Regular.ttf");
+
+/// Foreground and background as rgb
+fn colors(theme: Theme) -> ([u8; 3], [u8; 3]) {
+    match theme {
+        Theme::Dark => ([255, 255, 255], [0, 0, 0]),
+        Theme::Light => ([0, 0, 0], [255, 255, 255]),
+        Theme::HighContrast => ([255, 255, 0], [0, 0, 0]),
+    }
+}
+
+/// Renders screens with the bundled Roboto font
+pub struct Renderer {
+    font: FontRef<'static>,
+}
+
+impl Renderer {
+    pub fn new() -> Result<Self> {
+        FontRef::try_from_slice(ROBOTO_REGULAR)
+            .map(|font| Self { font })
+            .map_err(|_| Error::Font)
+    }
+
+    /// Scale with font size as the size of the em square, as in css
+    fn scale(&self, font_size: f32) -> PxScale {
+        let units_per_em = self.font.units_per_em().unwrap_or(1.0);
+        PxScale::from(font_size * self.font.height_unscaled() / units_per_em)
+    }
+
+    /// Screen drawn on a pixmap of width by height pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use lipl_display_common::LiplScreen;
+    /// use lipl_display_headless::Renderer;
+    /// let pixmap = Renderer::new()
+    ///     .unwrap()
+    ///     .render(&LiplScreen::new(true, 30.0), 320, 240)
+    ///     .unwrap();
+    /// assert_eq!((pixmap.width(), pixmap.height()), (320, 240));
+    /// ```
+    pub fn render(&self, screen: &LiplScreen, width: u32, height: u32) -> Result<Pixmap> {
+        let mut pixmap = Pixmap::new(width, height).ok_or(Error::Size(width, height))?;
+        let (foreground, [r, g, b]) = colors(screen.theme());
+        pixmap.fill(Color::from_rgba8(r, g, b, 255));
+
+        let layout = Layout::new(screen, &Viewport::new(width as f32, height as f32), self);
+        for span in layout.chords.iter().chain(&layout.lines) {
+            self.draw(&mut pixmap, span, layout.font_size, foreground);
+        }
+        self.draw(
+            &mut pixmap,
+            &layout.status,
+            layout.status_font_size,
+            foreground,
+        );
+        Ok(pixmap)
+    }
+
+    /// Screen as png image
+    pub fn render_png(&self, screen: &LiplScreen, width: u32, height: u32) -> Result<Vec<u8>> {
+        self.render(screen, width, height)?
+            .encode_png()
+            .map_err(|error| Error::Png(error.to_string()))
+    }
+
+    /// Screen saved as png image
+    pub fn save_png(
+        &self,
+        screen: &LiplScreen,
+        width: u32,
+        height: u32,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let png = self.render_png(screen, width, height)?;
+        std::fs::write(path, png)?;
+        Ok(())
+    }
+
+    /// Blend the glyphs of span into the opaque pixmap
+    fn draw(&self, pixmap: &mut Pixmap, span: &Span, font_size: f32, color: [u8; 3]) {
+        let font = self.font.as_scaled(self.scale(font_size));
+        let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+        let pixels = pixmap.pixels_mut();
+        let baseline = span.y + font.ascent();
+        let mut x = span.x;
+        let mut previous = None;
+        for c in span.text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                x += font.kern(previous, id);
+            }
+            previous = Some(id);
+            let glyph = id.with_scale_and_position(font.scale(), point(x, baseline));
+            x += font.h_advance(id);
+            let Some(outline) = self.font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|glyph_x, glyph_y, coverage| {
+                let x = bounds.min.x as i32 + glyph_x as i32;
+                let y = bounds.min.y as i32 + glyph_y as i32;
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    return;
+                }
+                let pixel = &mut pixels[(y * width + x) as usize];
+                let blend = |fg: u8, bg: u8| {
+                    (fg as f32 * coverage + bg as f32 * (1.0 - coverage)).round() as u8
+                };
+                if let Some(blended) = PremultipliedColorU8::from_rgba(
+                    blend(color[0], pixel.red()),
+                    blend(color[1], pixel.green()),
+                    blend(color[2], pixel.blue()),
+                    255,
+                ) {
+                    *pixel = blended;
+                }
+            });
+        }
+    }
+}
+
+impl Measure for Renderer {
+    fn text_width(&self, text: &str, font_size: f32) -> f32 {
+        let font = self.font.as_scaled(self.scale(font_size));
+        let mut previous = None;
+        text.chars()
+            .map(|c| {
+                let id = font.glyph_id(c);
+                let kern = previous.map(|previous| font.kern(previous, id));
+                previous = Some(id);
+                kern.unwrap_or_default() + font.h_advance(id)
+            })
+            .sum()
+    }
+
+    fn line_height(&self, font_size: f32) -> f32 {
+        let font = self.font.as_scaled(self.scale(font_size));
+        font.height() + font.line_gap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use lipl_display_common::{Command, HandleMessage, LiplScreen, Message, Part};
+    use tiny_skia::Pixmap;
+
+    use super::Renderer;
+
+    const WIDTH: u32 = 480;
+    const HEIGHT: u32 = 320;
+
+    fn screen(dark: bool, text: &str, status: &str) -> LiplScreen {
+        let mut screen = LiplScreen::new(dark, 24.0);
+        screen.handle_message(Message::Part(Part::chordpro(text)));
+        screen.handle_message(Message::Status(status.to_owned()));
+        screen
+    }
+
+    /// Compare with the golden image, or write it when UPDATE_GOLDEN is set
+    fn golden(name: &str, screen: &LiplScreen) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("golden")
+            .join(format!("{name}.png"));
+        let pixmap = Renderer::new()
+            .unwrap()
+            .render(screen, WIDTH, HEIGHT)
+            .unwrap();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            pixmap.save_png(&path).unwrap();
+            return;
+        }
+        let expected = Pixmap::load_png(&path).unwrap();
+        assert_eq!(pixmap.width(), expected.width());
+        assert_eq!(pixmap.height(), expected.height());
+        let different = pixmap
+            .data()
+            .iter()
+            .zip(expected.data())
+            .filter(|(actual, expected)| actual.abs_diff(**expected) > 2)
+            .count();
+        assert_eq!(different, 0, "{name} differs from {}", path.display());
+    }
+
+    #[test]
+    fn dark() {
+        golden(
+            "dark",
+            &screen(true, "Amazing grace, how sweet the sound", "1/3"),
+        );
+    }
+
+    #[test]
+    fn light() {
+        golden(
+            "light",
+            &screen(false, "Amazing grace, how sweet the sound", "1/3"),
+        );
+    }
+
+    #[test]
+    fn long_text() {
+        let text = "Amazing grace, how sweet the sound that saved a wretch like me. \
+            I once was lost, but now am found, was blind but now I see.\n"
+            .repeat(4);
+        golden(
+            "long_text",
+            &screen(true, &text, "Amazing Grace, couplet 1 van 6"),
+        );
+    }
+
+    #[test]
+    fn auto_fit_and_chords() {
+        let mut screen = screen(
+            true,
+            "[G]Amazing [C]grace, how [G]sweet the [D]sound\nThat [G]saved a [Em]wretch like [D]me",
+            "2/3",
+        );
+        screen.handle_message(Message::Command(Command::AutoFit(true)));
+        golden("auto_fit_and_chords", &screen);
+    }
+
+    #[test]
+    fn invalid_size() {
+        assert!(
+            Renderer::new()
+                .unwrap()
+                .render(&LiplScreen::default(), 0, 0)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use lipl_display_common::{Align, Command, HandleMessage, LiplScreen, Message, Part, Theme};
+use lipl_display_headless::{Error, Renderer};
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Text of the part
+    #[arg(long, default_value_t)]
+    text: String,
+    /// Parse the text as ChordPro, showing the chords between square brackets above the lyrics
+    #[arg(long)]
+    chords: bool,
+    /// Status line
+    #[arg(long, default_value_t)]
+    status: String,
+    /// Theme: dark, light or high-contrast
+    #[arg(long, default_value = "dark")]
+    theme: Theme,
+    /// Alignment: left, center or right
+    #[arg(long, default_value = "left")]
+    align: Align,
+    #[arg(long, default_value_t = 32)]
+    font_size: u32,
+    /// Choose the largest font size at which the text fits
+    #[arg(long)]
+    auto_fit: bool,
+    #[arg(long, default_value_t = 800)]
+    width: u32,
+    #[arg(long, default_value_t = 480)]
+    height: u32,
+    /// Png file to write
+    #[arg(short, long)]
+    output: PathBuf,
+}
+
+fn main() -> Result<(), Error> {
+    let args = Args::parse();
+    let mut screen = LiplScreen::default();
+    for command in [
+        Command::Theme(args.theme),
+        Command::Align(args.align),
+        Command::SetFontSize(args.font_size),
+        Command::AutoFit(args.auto_fit),
+    ] {
+        screen.handle_message(Message::Command(command));
+    }
+    let part = if args.chords {
+        Part::chordpro(&args.text)
+    } else {
+        args.text.into()
+    };
+    screen.handle_message(Message::Part(part));
+    screen.handle_message(Message::Status(args.status));
+    Renderer::new()?.save_png(&screen, args.width, args.height, args.output)
+}
@@ -1,5 +1,5 @@
 // pub(crate) const DEFAULT_PART: &str = "Even geduld a.u.b. ...";
-pub(crate) const DEFAULT_FONTSIZE: i32 = 30;
+pub(crate) const DEFAULT_FONTSIZE: f32 = 30.0;
 pub(crate) const DEFAULT_DARK: bool = true;
 pub(crate) const DEFAULT_LOG_DIR: &str = "/var/log/lipl";
 pub(crate) const CONFIG_FILE: &str = "/etc/lipl.toml";
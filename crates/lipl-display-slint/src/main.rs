@@ -1,10 +1,13 @@
+mod args;
 mod configuration;
 mod constant;
 mod handle_message;
+mod screen;
 
+use clap::Parser;
 use configuration::Config;
-use lipl_display_common::BackgroundThread;
-use lipl_gatt_bluer::ListenBluer;
+use lipl_display_common::{BackgroundThread, LiplScreen, Message};
+use lipl_message_source::ListenSource;
 use tracing::level_filters::LevelFilter;
 use tracing_appender::rolling::Rotation;
 use tracing_subscriber::{Layer, layer::SubscriberExt};
@@ -49,9 +52,12 @@ fn setup_logging(config: &Config) -> anyhow::Result<()> {
 
 #[cfg(target_os = "linux")]
 fn main() -> anyhow::Result<()> {
+    let args = args::Args::parse();
     let ui = LiplDisplay::new()?;
-    ui.set_fontsize(constant::DEFAULT_FONTSIZE);
-    ui.set_dark(constant::DEFAULT_DARK);
+    let screen = screen::Screen::new(LiplScreen::new(
+        constant::DEFAULT_DARK,
+        constant::DEFAULT_FONTSIZE,
+    ))?;
     let ui_handle = ui.as_weak();
 
     match configuration::Config::from_file(constant::CONFIG_FILE) {
@@ -59,17 +65,26 @@ fn main() -> anyhow::Result<()> {
             setup_logging(&config)?;
         }
         Err(error) => {
-            ui.set_part(
+            screen::lock(&screen).handle_message(Message::Part(
                 format!(
                     "Error: {error}\nFile {} missing or parsing error",
                     constant::CONFIG_FILE
                 )
                 .into(),
-            );
+            ));
         }
     }
 
-    let mut gatt = ListenBluer::new(handle_message::create_handle_message(ui_handle));
+    ui.on_resized({
+        let ui_handle = ui_handle.clone();
+        let screen = screen.clone();
+        move || screen::lock(&screen).draw(&ui_handle.unwrap())
+    });
+
+    let mut gatt = ListenSource::new(
+        args.source,
+        handle_message::create_handle_message(ui_handle, screen),
+    );
 
     ui.run()?;
     gatt.stop();
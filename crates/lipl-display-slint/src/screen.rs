@@ -0,0 +1,64 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use lipl_display_common::{
+    HandleMessage, LiplScreen, Message,
+    layout::{Layout, Span, Viewport},
+};
+use lipl_display_headless::Renderer;
+use slint::{ComponentHandle, ModelRc, VecModel};
+
+use crate::{LiplDisplay, TextSpan};
+
+/// Screen state shared by the message handler and the window
+pub(crate) type SharedScreen = Arc<Mutex<Screen>>;
+
+/// Screen measured with the bundled Roboto font, the default font of the window
+pub(crate) struct Screen {
+    screen: LiplScreen,
+    measure: Renderer,
+}
+
+impl Screen {
+    pub fn new(screen: LiplScreen) -> anyhow::Result<SharedScreen> {
+        Ok(Arc::new(Mutex::new(Self {
+            screen,
+            measure: Renderer::new()?,
+        })))
+    }
+
+    pub fn handle_message(&mut self, message: Message) {
+        self.screen.handle_message(message);
+    }
+
+    /// Show the layout of the screen for the current size of the window
+    pub fn draw(&self, ui: &LiplDisplay) {
+        let size = ui.window().size().to_logical(ui.window().scale_factor());
+        let layout = Layout::new(
+            &self.screen,
+            &Viewport::new(size.width, size.height),
+            &self.measure,
+        );
+        let span = |span: &Span, font_size: f32| TextSpan {
+            x: span.x,
+            y: span.y,
+            text: span.text.as_str().into(),
+            font_size,
+        };
+        let spans = layout
+            .chords
+            .iter()
+            .chain(&layout.lines)
+            .map(|s| span(s, layout.font_size))
+            .chain([span(&layout.status, layout.status_font_size)])
+            .collect::<Vec<_>>();
+
+        ui.set_dark(self.screen.dark);
+        ui.set_high_contrast(self.screen.high_contrast);
+        ui.set_spans(ModelRc::new(VecModel::from(spans)));
+    }
+}
+
+/// Screen for the caller, also after a panic of a previous holder
+pub(crate) fn lock(screen: &SharedScreen) -> MutexGuard<'_, Screen> {
+    screen.lock().unwrap_or_else(PoisonError::into_inner)
+}
@@ -0,0 +1,182 @@
+#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+
+use std::{
+    io,
+    sync::mpsc::{Receiver, Sender},
+    time::Duration,
+};
+
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use lipl_display_common::{
+    BackgroundThread, Command, HandleMessage, LiplScreen, Message, Theme,
+    layout::{Layout, Measure, Span, Viewport},
+};
+use lipl_message_source::ListenSource;
+use ratatui::{
+    DefaultTerminal, Frame,
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+
+mod args;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Text measured in terminal cells, whatever the font size
+struct Cells;
+
+impl Measure for Cells {
+    fn text_width(&self, text: &str, _font_size: f32) -> f32 {
+        text.chars().count() as f32
+    }
+
+    fn line_height(&self, _font_size: f32) -> f32 {
+        1.0
+    }
+}
+
+fn style(theme: Theme) -> Style {
+    match theme {
+        Theme::Dark => Style::new().fg(Color::White).bg(Color::Black),
+        Theme::Light => Style::new().fg(Color::Black).bg(Color::White),
+        Theme::HighContrast => Style::new().fg(Color::Yellow).bg(Color::Black),
+    }
+}
+
+fn create_callback(tx: Sender<Message>) -> impl Fn(Message) {
+    move |message| {
+        if let Err(error) = tx.send(message) {
+            log::error!("Error sending message: {error}");
+        }
+    }
+}
+
+fn put(buffer: &mut Buffer, area: Rect, span: &Span, style: Style) {
+    let x = area.x.saturating_add(span.x.round() as u16);
+    let y = area.y.saturating_add(span.y.round() as u16);
+    if x < area.right() && y < area.bottom() {
+        buffer.set_stringn(x, y, &span.text, usize::from(area.right() - x), style);
+    }
+}
+
+fn draw(frame: &mut Frame, screen: &LiplScreen) {
+    let area = frame.area();
+    let style = style(screen.theme());
+    let layout = Layout::new(
+        screen,
+        &Viewport::new(area.width.into(), area.height.into()),
+        &Cells,
+    );
+    let buffer = frame.buffer_mut();
+    buffer.set_style(area, style);
+    for span in layout.chords.iter().chain(&layout.lines) {
+        put(buffer, area, span, style);
+    }
+    put(
+        buffer,
+        area,
+        &layout.status,
+        style.add_modifier(Modifier::BOLD),
+    );
+}
+
+fn quit_requested() -> io::Result<bool> {
+    if !event::poll(POLL_INTERVAL)? {
+        return Ok(false);
+    }
+    Ok(match event::read()? {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+                || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+        }
+        _ => false,
+    })
+}
+
+fn run(terminal: &mut DefaultTerminal, rx: &Receiver<Message>) -> io::Result<()> {
+    let mut screen = LiplScreen::new(true, 1.0);
+    loop {
+        terminal.draw(|frame| draw(frame, &screen))?;
+        if quit_requested()? {
+            return Ok(());
+        }
+        while let Ok(message) = rx.try_recv() {
+            if [
+                Message::Command(Command::Exit),
+                Message::Command(Command::Poweroff),
+            ]
+            .contains(&message)
+            {
+                return Ok(());
+            }
+            screen.handle_message(message);
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let args = args::Args::parse();
+    let (tx, rx) = std::sync::mpsc::channel::<Message>();
+    let mut source = ListenSource::with_recording(args.source, args.record, create_callback(tx));
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &rx);
+    ratatui::restore();
+
+    source.stop();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use lipl_display_common::{Command, HandleMessage, LiplScreen, Message, Theme};
+    use ratatui::{Terminal, backend::TestBackend, style::Color};
+
+    use super::draw;
+
+    #[test]
+    fn wrapped_text_and_status() {
+        let mut screen = LiplScreen::new(true, 1.0);
+        screen.handle_message(Message::Part(
+            "Amazing grace, how sweet the sound that saved a wretch like me".into(),
+        ));
+        screen.handle_message(Message::Status("1/3".to_owned()));
+        let mut terminal = Terminal::new(TestBackend::new(40, 10)).unwrap();
+        terminal.draw(|frame| draw(frame, &screen)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let lines = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "                                        ",
+                "  Amazing grace, how sweet the sound    ",
+                "  that saved a wretch like me           ",
+                "                                        ",
+                "                                        ",
+                "                                        ",
+                "                                        ",
+                "                                        ",
+                "                                        ",
+                "  1/3                                   ",
+            ]
+        );
+    }
+
+    #[test]
+    fn light_theme() {
+        let mut screen = LiplScreen::new(true, 1.0);
+        screen.handle_message(Message::Command(Command::Theme(Theme::Light)));
+        let mut terminal = Terminal::new(TestBackend::new(10, 4)).unwrap();
+        terminal.draw(|frame| draw(frame, &screen)).unwrap();
+        let cell = &terminal.backend().buffer()[(0, 0)];
+        assert_eq!((cell.fg, cell.bg), (Color::Black, Color::White));
+    }
+}
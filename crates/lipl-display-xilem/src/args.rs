@@ -0,0 +1,19 @@
+use std::sync::OnceLock;
+
+use clap::Parser;
+use lipl_message_source::Source;
+
+static ARGS: OnceLock<Args> = OnceLock::new();
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, daemon, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
+}
+
+/// Arguments parsed once, available to components and tasks
+pub fn args() -> &'static Args {
+    ARGS.get_or_init(Args::parse)
+}
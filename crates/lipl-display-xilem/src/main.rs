@@ -1,20 +1,31 @@
-use futures_util::TryStreamExt;
-use lipl_display_common::{Command, HandleMessage, LiplScreen, Message};
+use futures_util::StreamExt;
+use lipl_display_common::{
+    Command, HandleMessage, LiplScreen, Message, MessageSource,
+    layout::{Layout, Span, Viewport},
+};
+use lipl_display_headless::Renderer;
 use std::str;
-use std::time::Duration;
+use std::sync::LazyLock;
 use xilem::core::{MessageProxy, fork};
 use xilem::style::{Background, Style};
-use xilem::view::{
-    Axis, CrossAxisAlignment, GridExt, GridParams, MainAxisAlignment, flex, grid, label, sized_box,
-    task,
-};
+use xilem::view::{Alignment, label, sized_box, task, transformed, zstack};
+use xilem::winit::dpi::LogicalSize;
 use xilem::{Color, EventLoop, WidgetView, WindowOptions, Xilem, tokio};
 
+mod args;
+
 const APP_TITLE: &str = "Lipl Display";
 const WAIT_MESSAGE: &str = "Even geduld a.u.b. ...";
 const ROBOTO_FONT: &[u8] = include_bytes!("../../../font/Roboto-Regular.ttf");
 const DEFAULT_DARK: bool = true;
 const DEFAULT_FONT_SIZE: f32 = 22.0;
+/// Size of the window, which cannot be resized, so the layout is made for it
+const WINDOW_WIDTH: f32 = 800.0;
+const WINDOW_HEIGHT: f32 = 480.0;
+
+/// Measurement with the bundled Roboto font, the font the labels are drawn with
+static MEASURE: LazyLock<Renderer> =
+    LazyLock::new(|| Renderer::new().expect("Bundled font is valid"));
 
 trait LiplScreenExt {
     fn bg_color(&self) -> Color;
@@ -31,7 +42,9 @@ impl LiplScreenExt for LiplScreen {
     }
 
     fn fg_color(&self) -> Color {
-        if self.dark {
+        if self.high_contrast {
+            Color::from_rgb8(255, 255, 0)
+        } else if self.dark {
             Color::WHITE
         } else {
             Color::BLACK
@@ -40,47 +53,44 @@ impl LiplScreenExt for LiplScreen {
 }
 
 fn display(screen: &mut LiplScreen) -> impl WidgetView<LiplScreen> + use<> {
-    sized_box(grid(
-        (
-            flex(
-                Axis::Horizontal,
-                label(screen.text.clone())
-                    .text_size(screen.font_size)
-                    .font("Roboto")
-                    .color(screen.fg_color()),
-            )
-            .direction(Axis::Horizontal)
-            .main_axis_alignment(MainAxisAlignment::Center)
-            .cross_axis_alignment(CrossAxisAlignment::Center)
-            .grid_item(GridParams::new(0, 0, 1, 11)),
-            flex(
-                Axis::Horizontal,
-                label(screen.status.as_str())
-                    .text_size(screen.font_size)
-                    .color(screen.fg_color()),
-            )
-            .direction(Axis::Horizontal)
-            .main_axis_alignment(MainAxisAlignment::Center)
-            .grid_pos(0, 11),
-        ),
-        1,
-        12,
-    ))
-    .background(Background::Color(screen.bg_color()))
+    let layout = Layout::new(
+        screen,
+        &Viewport::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+        &*MEASURE,
+    );
+    let color = screen.fg_color();
+    let span = |span: &Span, font_size: f32| {
+        transformed(
+            label(span.text.clone())
+                .text_size(font_size)
+                .font("Roboto")
+                .color(color),
+        )
+        .translate((span.x.into(), span.y.into()))
+    };
+    let spans = layout
+        .chords
+        .iter()
+        .chain(&layout.lines)
+        .map(|s| span(s, layout.font_size))
+        .chain([span(&layout.status, layout.status_font_size)])
+        .collect::<Vec<_>>();
+
+    sized_box(zstack(spans).alignment(Alignment::TopLeading))
+        .expand()
+        .background(Background::Color(screen.bg_color()))
 }
 
 async fn background_task(proxy: MessageProxy<Message>) {
     let result = async {
         tracing::info!("Starting task");
-        let r = json_lines::file_reader("/home/paul/Code/dart/lipl_display/lipl-gatt-input.txt")
-            .await?;
-        tracing::info!("input from file ok");
-        let mut s = json_lines::lines::<Message, _>(r);
-        while let Some(message) = s.try_next().await? {
+        let mut s = lipl_message_source::open(&args::args().source).await?;
+        tracing::info!("Receiving messages from {}", s.name());
+        while let Some(message) = s.next().await {
             tracing::info!("Received message: {}", message);
             proxy.message(message)?;
-            tokio::time::sleep(Duration::from_secs(1)).await;
         }
+        s.stop().await;
         Ok::<(), Box<dyn std::error::Error>>(())
     }
     .await;
@@ -118,6 +128,7 @@ fn app_logic(screen: &mut LiplScreen) -> impl WidgetView<LiplScreen> + use<> {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    args::args();
     tracing_subscriber::fmt::init();
     Xilem::new_simple(
         LiplScreen {
@@ -125,9 +136,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             status: WAIT_MESSAGE.into(),
             font_size: DEFAULT_FONT_SIZE,
             dark: DEFAULT_DARK,
+            ..Default::default()
         },
         app_logic,
-        WindowOptions::new(APP_TITLE),
+        WindowOptions::new(APP_TITLE)
+            .with_initial_inner_size(LogicalSize::new(WINDOW_WIDTH, WINDOW_HEIGHT))
+            .with_resizable(false),
     )
     .with_font(ROBOTO_FONT.to_vec())
     .run_in(EventLoop::with_user_event())
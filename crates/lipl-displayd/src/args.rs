@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use lipl_message_source::Source;
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Source of messages: bluer, zbus, stdin, file:<path>, follow:<path>, socket:<path>, process:<command>, replay:<path> or osc:<address>
+    #[arg(short, long, default_value_t = Source::default())]
+    pub source: Source,
+    /// Unix socket for the displays, `lipl-displayd.sock` in `$XDG_RUNTIME_DIR` by default
+    #[arg(long)]
+    pub socket: Option<PathBuf>,
+    /// Record received messages to a file, for replay with replay:<path>
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+    /// Address for the http api, as 0.0.0.0:8080
+    #[cfg(feature = "http")]
+    #[arg(long)]
+    pub http: Option<std::net::SocketAddr>,
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[error("Source error: {0}")]
+    Source(#[from] lipl_message_source::Error),
+
+    #[cfg(feature = "http")]
+    #[error("Access config: {0}")]
+    Access(#[from] lipl_display_common::Error),
+
+    #[error("Json serialization: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Daemon already listening on {0}")]
+    AlreadyRunning(PathBuf),
+}
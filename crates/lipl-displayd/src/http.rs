@@ -0,0 +1,171 @@
+//! Http api for controllers on the local network without bluetooth
+//!
+//! | Request         | Effect                                                    |
+//! |-----------------|-----------------------------------------------------------|
+//! | `POST /message` | handle a json serialized message, as `{"part":"Hello"}`   |
+//! | `GET /screen`   | current screen as json                                    |
+//! | `GET /events`   | server-sent events with every message handled from now on |
+//!
+//! Posted messages are checked with [`Access::accept_local`], exit and poweroff are refused with 403.
+
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+};
+use futures_util::{Stream, stream};
+use lipl_display_common::{Error, LiplScreen, Message, access::Access};
+use log::warn;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc, watch,
+};
+
+#[derive(Clone)]
+pub struct AppState {
+    /// Messages for the daemon to handle as if they came from the source
+    pub injected: mpsc::Sender<Message>,
+    pub screen: watch::Receiver<LiplScreen>,
+    pub messages: broadcast::Sender<Message>,
+    pub access: Arc<Access>,
+}
+
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/message", post(post_message))
+        .route("/screen", get(get_screen))
+        .route("/events", get(events))
+        .with_state(state)
+}
+
+async fn post_message(State(state): State<AppState>, Json(message): Json<Message>) -> StatusCode {
+    let message = match state.access.accept_local(message) {
+        Ok(message) => message,
+        Err(error) => {
+            let count = state.access.count_rejection(&error);
+            warn!("Posted message rejected, {count} times so far: {error}");
+            return match error {
+                Error::TooLong { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+                Error::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+                _ => StatusCode::FORBIDDEN,
+            };
+        }
+    };
+    match state.injected.send(message).await {
+        Ok(_) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+async fn get_screen(State(state): State<AppState>) -> Json<LiplScreen> {
+    Json(state.screen.borrow().clone())
+}
+
+async fn events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = stream::unfold(state.messages.subscribe(), |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(message) => match Event::default().json_data(&message) {
+                    Ok(event) => return Some((Ok(event), receiver)),
+                    Err(error) => warn!("Error serializing event: {error}"),
+                },
+                Err(RecvError::Lagged(count)) => warn!("Event stream missed {count} messages"),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use std::sync::Arc;
+
+    use lipl_display_common::{Message, access::Access};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        sync::mpsc,
+    };
+
+    use super::{AppState, router};
+    use crate::server::Server;
+
+    async fn request(address: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn post(body: &str) -> String {
+        format!(
+            "POST /message HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    /// Read from stream until the received text contains pattern
+    async fn read_until(stream: &mut TcpStream, pattern: &str) -> String {
+        let mut received = String::new();
+        let mut buffer = [0; 1024];
+        while !received.contains(pattern) {
+            let count = stream.read(&mut buffer).await.unwrap();
+            assert!(count > 0, "Connection closed before {pattern}");
+            received.push_str(&String::from_utf8_lossy(&buffer[..count]));
+        }
+        received
+    }
+
+    #[tokio::test]
+    async fn api() {
+        let mut server = Server::default();
+        let (injected, mut received) = mpsc::channel(4);
+        let state = AppState {
+            injected,
+            screen: server.screen(),
+            messages: server.sender(),
+            access: Arc::new(Access::default()),
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await });
+
+        let response = request(address, &post(r#"{"part":"Amazing grace"}"#)).await;
+        assert!(response.starts_with("HTTP/1.1 202"));
+        let message = received.recv().await.unwrap();
+        assert_eq!(message, Message::Part("Amazing grace".into()));
+        server.publish(message);
+
+        let response = request(address, &post(r#"{"song":"Amazing grace"}"#)).await;
+        assert!(response.starts_with("HTTP/1.1 422"));
+        let response = request(address, &post(r#"{"command":"Exit"}"#)).await;
+        assert!(response.starts_with("HTTP/1.1 403"));
+
+        let response = request(
+            address,
+            "GET /screen HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await;
+        assert!(response.contains(r#""text":"Amazing grace""#));
+
+        let mut events = TcpStream::connect(address).await.unwrap();
+        events
+            .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let headers = read_until(&mut events, "\r\n\r\n").await;
+        assert!(headers.contains("text/event-stream"));
+        server.publish(Message::Status("1/3".to_owned()));
+        read_until(&mut events, r#"data: {"status":"1/3"}"#).await;
+    }
+}
@@ -0,0 +1,96 @@
+#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+
+use clap::Parser;
+use futures_util::{SinkExt, StreamExt};
+use lipl_display_common::{Message, MessageSource};
+use lipl_message_source::daemon_socket;
+use log::{error, info};
+use tokio::signal::unix::{SignalKind, signal};
+
+use error::Error;
+use server::Server;
+
+mod args;
+mod error;
+#[cfg(feature = "http")]
+mod http;
+mod server;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let args = args::Args::parse();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let path = args.socket.unwrap_or_else(daemon_socket);
+    let listener = server::bind(&path)?;
+    info!("Listening for displays on {}", path.display());
+
+    let mut messages = match &args.record {
+        Some(record) => lipl_message_source::open_recording(&args.source, record).await?,
+        None => lipl_message_source::open(&args.source).await?,
+    };
+    info!("Receiving messages from {}", messages.name());
+
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut server = Server::default();
+
+    // Messages from controllers other than the source, handled the same way
+    let (injected_sender, mut injected) = tokio::sync::mpsc::channel::<Message>(16);
+    #[cfg(feature = "http")]
+    if let Some(address) = args.http {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        info!("Listening for http requests on {address}");
+        let router = http::router(http::AppState {
+            injected: injected_sender.clone(),
+            screen: server.screen(),
+            messages: server.sender(),
+            access: std::sync::Arc::new(lipl_display_common::access::Access::load()?),
+        });
+        tokio::spawn(async move {
+            if let Err(error) = axum::serve(listener, router).await {
+                error!("Http server stopped: {error}");
+            }
+        });
+    }
+    drop(injected_sender);
+    // A gatt peripheral handles injected messages itself, so its screen characteristic shows them
+    let mut injector = messages.injector();
+
+    loop {
+        tokio::select! {
+            message = messages.next() => match message {
+                Some(message) => server.publish(message),
+                None => break,
+            },
+            Some(message) = injected.recv() => match injector.as_mut() {
+                Some(injector) => {
+                    if let Err(error) = injector.send(message).await {
+                        error!("Error injecting message into {}: {error}", messages.name());
+                    }
+                }
+                None => server.publish(message),
+            },
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    info!("Display connected");
+                    let (snapshot, receiver) = server.subscribe();
+                    tokio::spawn(async move {
+                        if let Err(error) = server::serve(stream, snapshot, receiver).await {
+                            info!("Display disconnected: {error}");
+                        }
+                    });
+                }
+                Err(error) => error!("Error accepting display: {error}"),
+            },
+            _ = interrupt.recv() => break,
+            _ = terminate.recv() => break,
+        }
+    }
+
+    messages.stop().await;
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
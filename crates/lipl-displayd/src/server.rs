@@ -0,0 +1,159 @@
+use std::path::Path;
+
+use lipl_display_common::{HandleMessage, LiplScreen, Message, Pager};
+use log::{debug, warn};
+use tokio::{
+    io::{AsyncWrite, AsyncWriteExt},
+    net::UnixListener,
+    sync::{
+        broadcast::{self, Receiver, Sender, error::RecvError},
+        watch,
+    },
+};
+
+use crate::{Error, Result};
+
+/// Messages kept for a client that does not keep up
+const CAPACITY: usize = 64;
+
+/// Listen on path, replacing a socket file left behind by a daemon that is gone
+pub fn bind(path: &Path) -> Result<UnixListener> {
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(Error::AlreadyRunning(path.to_owned()));
+        }
+        std::fs::remove_file(path)?;
+    }
+    Ok(UnixListener::bind(path)?)
+}
+
+/// State of the screen and the clients to publish to
+pub struct Server {
+    screen: watch::Sender<LiplScreen>,
+    pager: Pager,
+    sender: Sender<Message>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self {
+            screen: watch::Sender::new(LiplScreen::default()),
+            pager: Pager::default(),
+            sender,
+        }
+    }
+}
+
+impl Server {
+    /// Update the screen and pass the message on to the clients
+    ///
+    /// Playlists are resolved here, so clients receive parts and statuses.
+    pub fn publish(&mut self, message: Message) {
+        for message in self.pager.resolve(message) {
+            self.screen
+                .send_modify(|screen| screen.handle_message(message.clone()));
+            if self.sender.send(message).is_err() {
+                debug!("No clients connected");
+            }
+        }
+    }
+
+    /// Messages to bring a new client up to date and the receiver for the messages after that
+    ///
+    /// The snapshot holds every setting, also the defaults, as a display may have been started with others.
+    pub fn subscribe(&self) -> (Vec<Message>, Receiver<Message>) {
+        let snapshot = self.screen.borrow().snapshot();
+        (snapshot, self.sender.subscribe())
+    }
+
+    /// Current screen, updated with every published message
+    #[cfg(feature = "http")]
+    pub fn screen(&self) -> watch::Receiver<LiplScreen> {
+        self.screen.subscribe()
+    }
+
+    /// Sender of the published messages, to subscribe to later
+    #[cfg(feature = "http")]
+    pub fn sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Message) -> Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+    Ok(())
+}
+
+/// Write the snapshot and then every published message as json lines
+///
+/// Returns when the daemon stops or the client has gone.
+pub async fn serve<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    snapshot: Vec<Message>,
+    mut receiver: Receiver<Message>,
+) -> Result<()> {
+    for message in &snapshot {
+        write_message(&mut writer, message).await?;
+    }
+    loop {
+        match receiver.recv().await {
+            Ok(message) => write_message(&mut writer, &message).await?,
+            Err(RecvError::Lagged(count)) => warn!("Client missed {count} messages"),
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use lipl_display_common::{Command, LiplScreen, Message, Playlist, Theme};
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    use super::{Server, serve};
+
+    #[tokio::test]
+    async fn snapshot_then_updates() {
+        let mut server = Server::default();
+        server.publish(Message::Playlist(Playlist {
+            id: None,
+            title: None,
+            parts: vec!["Amazing grace".to_owned(), "How sweet".to_owned()],
+        }));
+
+        let (client, daemon) = tokio::io::duplex(4096);
+        let (snapshot, receiver) = server.subscribe();
+        let task = tokio::spawn(serve(daemon, snapshot, receiver));
+        server.publish(Message::Command(Command::Next));
+        drop(server);
+        task.await.unwrap().unwrap();
+
+        let mut lines = BufReader::new(client).lines();
+        let mut messages = vec![];
+        while let Some(line) = lines.next_line().await.unwrap() {
+            messages.push(serde_json::from_str::<Message>(&line).unwrap());
+        }
+        // Settings with their default value are part of the snapshot too
+        let defaults = LiplScreen::default();
+        assert_eq!(
+            messages[..4],
+            [
+                Message::Command(Command::Theme(Theme::Light)),
+                Message::Command(Command::Align(defaults.align)),
+                Message::Command(Command::SetFontSize(defaults.font_size.round() as u32)),
+                Message::Command(Command::AutoFit(defaults.auto_fit)),
+            ]
+        );
+        assert_eq!(messages[4], Message::Part("Amazing grace".into()));
+        assert_eq!(messages[5], Message::Status("1/2".to_owned()));
+        assert_eq!(
+            messages[messages.len() - 2..],
+            [
+                Message::Part("How sweet".into()),
+                Message::Status("2/2".to_owned())
+            ]
+        );
+    }
+}
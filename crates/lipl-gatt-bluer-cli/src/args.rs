@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, ValueEnum};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum Format {
+    /// One json serialized message per line
+    #[default]
+    Json,
+    /// Indented json
+    Pretty,
+    /// Human readable
+    Text,
+}
+
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// Bluetooth adapter, as hci0, the default adapter if not given
+    #[arg(long)]
+    pub adapter: Option<String>,
+    /// Config file with the devices that may write in its access table
+    #[arg(long, default_value = lipl_display_common::access::CONFIG_FILE)]
+    pub config: PathBuf,
+    /// Name used in advertising
+    #[arg(long, default_value = lipl_display_common::LOCAL_NAME)]
+    pub local_name: String,
+    /// File the messages are written to instead of standard output
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    #[arg(short, long, value_enum, default_value_t = Format::default())]
+    pub format: Format,
+    /// Log a poweroff command instead of powering off
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Milliseconds between a poweroff command and the poweroff
+    #[arg(long, default_value_t = 1000)]
+    pub poweroff_delay: u64,
+    /// Exit after an exit or poweroff command
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    pub exit_on_command: bool,
+}
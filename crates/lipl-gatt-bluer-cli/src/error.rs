@@ -6,6 +6,9 @@ pub enum Error {
     #[error("Json: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("Config: {0}")]
+    Config(#[from] lipl_display_common::Error),
+
     #[error("Gatt: {0}")]
     Gatt(#[from] lipl_gatt_bluer::Error),
 
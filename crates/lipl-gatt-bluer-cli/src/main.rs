@@ -1,11 +1,22 @@
+#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+
+use std::io::Write;
+use std::sync::Arc;
+
+use clap::Parser;
 use futures_util::{StreamExt, pin_mut};
-use lipl_display_common::{Command, Message};
-use lipl_gatt_bluer::listen_stream;
+use lipl_display_common::{
+    Command, Message,
+    access::{Access, AccessConfig},
+};
+use lipl_gatt_bluer::{Config, listen_stream_with};
 use login_poweroff_reboot::{Shutdown, shutdown};
 
+use args::Args;
 use error::{ErrInto, Error};
-use signal::{INTERRUPT, SignalKind, TERMINATE, combine_signals};
+use signal::{INTERRUPT, RESET_CLAIM, SignalKind, TERMINATE, combine_signals};
 
+mod args;
 mod error;
 mod out;
 mod signal;
@@ -14,23 +25,49 @@ const EXIT_ON_SIGNALS: [SignalKind; 2] = [INTERRUPT, TERMINATE];
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<(), Error> {
-    let mut out = out::Out::default();
+    let args = Args::parse();
+    // Logging to stderr, keeping stdout for the messages
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(std::fs::File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut out = out::Out::new(writer, args.format);
 
-    let stream = listen_stream().await.map(|s| s.fuse())?;
+    let access = Arc::new(Access::new(AccessConfig::load(&args.config)?));
+    let config = Config {
+        adapter: args.adapter.clone(),
+        local_name: args.local_name.clone(),
+        access: access.clone(),
+    };
+    let stream = listen_stream_with(&config).await.map(|s| s.fuse())?;
     pin_mut!(stream);
 
     let combined_signal = combine_signals(EXIT_ON_SIGNALS)?;
     pin_mut!(combined_signal);
+    let reset_claim = combine_signals([RESET_CLAIM])?;
+    pin_mut!(reset_claim);
 
     loop {
         tokio::select! {
             _ = combined_signal.next() => { break; }
+            _ = reset_claim.next() => {
+                log::info!("Claim reset");
+                access.reset_claim();
+            }
             message = stream.select_next_some() => {
-                out.send_json(&message)?;
+                out.send(&message)?;
                 if message == Message::Command(Command::Poweroff) {
-                    shutdown(Shutdown::Poweroff)(1000).err_into()?;
-                    break;
-                } else if message == Message::Command(Command::Exit) {
+                    if args.dry_run {
+                        log::info!("Dry run, not powering off in {} ms", args.poweroff_delay);
+                    } else {
+                        shutdown(Shutdown::Poweroff)(args.poweroff_delay).err_into()?;
+                    }
+                    if args.exit_on_command {
+                        break;
+                    }
+                } else if message == Message::Command(Command::Exit) && args.exit_on_command {
                     break;
                 }
             }
@@ -1,6 +1,7 @@
-use serde::Serialize;
+use lipl_display_common::Message;
 use std::io::{Stdout, Write};
 
+use crate::args::Format;
 use crate::error::{ErrInto, Error};
 
 pub struct Out<W = Stdout>
@@ -8,13 +9,12 @@ where
     W: Write,
 {
     out: W,
+    format: Format,
 }
 
 impl Default for Out {
     fn default() -> Self {
-        Self {
-            out: std::io::stdout(),
-        }
+        Self::new(std::io::stdout(), Format::default())
     }
 }
 
@@ -22,15 +22,46 @@ impl<W> Out<W>
 where
     W: Write,
 {
-    pub fn send_json<S: Serialize>(&mut self, serializable: &S) -> Result<(), Error> {
-        serde_json::to_string(serializable)
+    pub fn new(out: W, format: Format) -> Self {
+        Self { out, format }
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), Error> {
+        let line = match self.format {
+            Format::Json => serde_json::to_string(message)?,
+            Format::Pretty => serde_json::to_string_pretty(message)?,
+            Format::Text => message.to_string(),
+        };
+        self.out
+            .write_all((line + "\n").as_bytes())
+            .and_then(|_| self.out.flush())
             .err_into()
-            .and_then(|json| {
-                self.out
-                    .write_all((json + "\n").as_bytes())
-                    .and_then(|_| self.out.flush())
-                    .err_into()
-            })
-        // self.out.flush().err_into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Out;
+    use crate::args::Format;
+    use lipl_display_common::{Command, Message};
+
+    fn written(format: Format) -> String {
+        let mut out = Out::new(vec![], format);
+        out.send(&Message::Status("1/2".to_owned())).unwrap();
+        out.send(&Message::Command(Command::Dark)).unwrap();
+        String::from_utf8(out.out).unwrap()
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(
+            written(Format::Json),
+            "{\"status\":\"1/2\"}\n{\"command\":\"Dark\"}\n"
+        );
+        assert_eq!(
+            written(Format::Pretty),
+            "{\n  \"status\": \"1/2\"\n}\n{\n  \"command\": \"Dark\"\n}\n"
+        );
+        assert_eq!(written(Format::Text), "Status: 1/2\nCommand: d\n");
     }
 }
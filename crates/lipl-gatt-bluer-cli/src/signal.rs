@@ -8,6 +8,7 @@ pub use tokio::signal::unix::SignalKind;
 
 pub const INTERRUPT: SignalKind = SignalKind::interrupt();
 pub const TERMINATE: SignalKind = SignalKind::terminate();
+pub const RESET_CLAIM: SignalKind = SignalKind::user_defined1();
 
 pub fn combine_signals<S>(signals: S) -> Result<SelectAll<SignalStream>, Error>
 where
@@ -1,37 +1,145 @@
 use bluer::Uuid;
+use bluer::gatt::WriteOp;
 use bluer::gatt::local::{
-    Characteristic, CharacteristicWrite, CharacteristicWriteMethod, ReqError,
+    Characteristic, CharacteristicNotify, CharacteristicNotifyMethod, CharacteristicRead,
+    CharacteristicWrite, CharacteristicWriteMethod, ReqError,
 };
 use futures_channel::mpsc;
 use futures_util::{FutureExt, SinkExt};
-use lipl_display_common::Message;
-use std::sync::Arc;
+use lipl_display_common::{
+    Message, Security,
+    access::Access,
+    framing::{LONG_WRITE_DELAY, Reassembler},
+};
+use log::{error, warn};
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
 use tokio::sync::Mutex;
 
-pub fn write_no_response_characteristic(
+use crate::screen::ScreenState;
+
+/// Writes on a single characteristic, passed on as messages once complete and accepted
+#[derive(Clone)]
+pub struct WriteHandler {
     uuid: Uuid,
-    value_write: Arc<Mutex<Vec<u8>>>,
+    reassembler: Arc<Mutex<Reassembler>>,
+    /// Parts of long writes received, only the last part executes the long write
+    parts: Arc<AtomicU64>,
+    screen: Arc<ScreenState>,
+    access: Arc<Access>,
     sender: mpsc::Sender<Message>,
+}
+
+impl WriteHandler {
+    pub fn new(
+        uuid: Uuid,
+        screen: Arc<ScreenState>,
+        access: Arc<Access>,
+        sender: mpsc::Sender<Message>,
+    ) -> Self {
+        Self {
+            uuid,
+            reassembler: Default::default(),
+            parts: Default::default(),
+            screen,
+            access,
+            sender,
+        }
+    }
+
+    /// Handle a write from the device with address
+    ///
+    /// Parts of a long write are executed when no part follows within [`LONG_WRITE_DELAY`].
+    pub async fn write(
+        &self,
+        address: &str,
+        offset: usize,
+        long_write: bool,
+        value: &[u8],
+    ) -> Result<(), ReqError> {
+        let uuid = self.uuid;
+        if !self.access.authorize(Some(address)) {
+            warn!("Write from {address} not authorized");
+            return Err(ReqError::NotAuthorized);
+        }
+
+        let mut reassembler = self.reassembler.lock().await;
+        let value = if long_write {
+            reassembler.prepare(offset, value).map(|_| None)
+        } else {
+            reassembler.push(offset, value)
+        }
+        .map_err(|error| {
+            error!("Cannot handle write request for {uuid}: {error}");
+            req_error(&error)
+        })?;
+        drop(reassembler);
+
+        if long_write {
+            let part = self.parts.fetch_add(1, Ordering::SeqCst) + 1;
+            let handler = self.clone();
+            let address = address.to_owned();
+            tokio::spawn(async move {
+                tokio::time::sleep(LONG_WRITE_DELAY).await;
+                if handler.parts.load(Ordering::SeqCst) == part {
+                    let value = handler.reassembler.lock().await.execute();
+                    if let Err(error) = handler.accept(&address, value).await {
+                        error!("Cannot execute long write to {uuid}: {error:?}");
+                    }
+                }
+            });
+        }
+        self.accept(address, value).await
+    }
+
+    async fn accept(&self, address: &str, value: Option<Vec<u8>>) -> Result<(), ReqError> {
+        let uuid = self.uuid;
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let received = std::str::from_utf8(&value).map_err(|error| {
+            warn!("Write to {uuid} from {address} rejected: {error}");
+            ReqError::Failed
+        })?;
+        let message = self
+            .access
+            .accept(Some(address), uuid, received)
+            .map_err(|error| rejected(&self.access, uuid, address, error))?;
+        self.screen.update(message.clone()).await;
+        self.sender
+            .clone()
+            .send(message)
+            .await
+            .map_err(|_| ReqError::Failed)
+    }
+}
+
+pub fn write_no_response_characteristic(
+    handler: WriteHandler,
+    security: Security,
 ) -> Characteristic {
     Characteristic {
-        uuid,
+        uuid: handler.uuid,
         write: Some(CharacteristicWrite {
             write: true,
             write_without_response: true,
-            method: CharacteristicWriteMethod::Fun(Box::new(move |new_value, _| {
-                let value = value_write.clone();
-                let mut s = sender.clone();
+            encrypt_write: security == Security::Encrypted,
+            encrypt_authenticated_write: security == Security::Authenticated,
+            method: CharacteristicWriteMethod::Fun(Box::new(move |new_value, request| {
+                let handler = handler.clone();
                 async move {
-                    let mut value = value.lock().await;
-                    let send_value: Vec<u8> = new_value.to_vec();
-                    *value = new_value;
-
-                    if let Ok(received) = std::str::from_utf8(&send_value) {
-                        let message =
-                            Message::try_from((received, uuid)).map_err(|_| ReqError::Failed)?;
-                        s.send(message).await.map_err(|_| ReqError::Failed)?;
-                    }
-                    Ok(())
+                    // Parts of a long write are reliable writes or have an offset
+                    let long_write = request.op_type == WriteOp::Reliable || request.offset > 0;
+                    handler
+                        .write(
+                            &request.device_address.to_string(),
+                            request.offset.into(),
+                            long_write,
+                            &new_value,
+                        )
+                        .await
                 }
                 .boxed()
             })),
@@ -41,3 +149,181 @@ pub fn write_no_response_characteristic(
         ..Default::default()
     }
 }
+
+/// Gatt error replied for a rejected write
+fn req_error(error: &lipl_display_common::Error) -> ReqError {
+    use lipl_display_common::Error;
+    match error {
+        Error::InvalidOffset(_) => ReqError::InvalidOffset,
+        Error::TooLong { .. } => ReqError::InvalidValueLength,
+        Error::RateLimited => ReqError::NotPermitted,
+        Error::Unsigned | Error::Signature | Error::Replayed(_) => ReqError::NotAuthorized,
+        _ => ReqError::Failed,
+    }
+}
+
+/// Log and count a rejected write
+fn rejected(
+    access: &Access,
+    uuid: Uuid,
+    address: &str,
+    error: lipl_display_common::Error,
+) -> ReqError {
+    let count = access.count_rejection(&error);
+    warn!("Write to {uuid} from {address} rejected, {count} times so far: {error}");
+    req_error(&error)
+}
+
+pub fn read_notify_characteristic(uuid: Uuid, screen: Arc<ScreenState>) -> Characteristic {
+    let notify_screen = screen.clone();
+    Characteristic {
+        uuid,
+        read: Some(CharacteristicRead {
+            read: true,
+            fun: Box::new(move |request| {
+                let screen = screen.clone();
+                async move {
+                    let value = screen.json().await;
+                    value
+                        .get(usize::from(request.offset)..)
+                        .map(<[u8]>::to_vec)
+                        .ok_or(ReqError::InvalidOffset)
+                }
+                .boxed()
+            }),
+            ..Default::default()
+        }),
+        notify: Some(CharacteristicNotify {
+            notify: true,
+            method: CharacteristicNotifyMethod::Fun(Box::new(move |notifier| {
+                let screen = notify_screen.clone();
+                async move {
+                    screen.subscribe(notifier).await;
+                }
+                .boxed()
+            })),
+            ..Default::default()
+        }),
+
+        ..Default::default()
+    }
+}
+
+pub fn read_characteristic(uuid: Uuid, value: Vec<u8>) -> Characteristic {
+    let value = Arc::new(value);
+    Characteristic {
+        uuid,
+        read: Some(CharacteristicRead {
+            read: true,
+            fun: Box::new(move |request| {
+                let value = value.clone();
+                async move {
+                    value
+                        .get(usize::from(request.offset)..)
+                        .map(<[u8]>::to_vec)
+                        .ok_or(ReqError::InvalidOffset)
+                }
+                .boxed()
+            }),
+            ..Default::default()
+        }),
+
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{WriteHandler, write_no_response_characteristic};
+    use crate::screen::ScreenState;
+    use bluer::Uuid;
+    use bluer::gatt::local::ReqError;
+    use futures_channel::mpsc::{Receiver, channel};
+    use futures_util::StreamExt;
+    use lipl_display_common::{
+        CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_PLAYLIST_UUID, CHARACTERISTIC_STATUS_UUID,
+        CHARACTERISTIC_TEXT_UUID, Message, Security, access::Access,
+    };
+    use std::sync::Arc;
+
+    const ADDRESS: &str = "AA:BB:CC:DD:EE:FF";
+
+    fn handler_for(uuid: Uuid) -> (WriteHandler, Receiver<Message>) {
+        let (sender, receiver) = channel(10);
+        let handler = WriteHandler::new(
+            uuid,
+            Arc::new(ScreenState::default()),
+            Arc::new(Access::default()),
+            sender,
+        );
+        (handler, receiver)
+    }
+
+    fn handler() -> (WriteHandler, Receiver<Message>) {
+        handler_for(CHARACTERISTIC_STATUS_UUID)
+    }
+
+    #[test]
+    fn flags() {
+        for uuid in [
+            CHARACTERISTIC_TEXT_UUID,
+            CHARACTERISTIC_STATUS_UUID,
+            CHARACTERISTIC_COMMAND_UUID,
+            CHARACTERISTIC_PLAYLIST_UUID,
+        ] {
+            let characteristic =
+                write_no_response_characteristic(handler_for(uuid).0, Security::default_for(uuid));
+            assert_eq!(characteristic.uuid, uuid);
+            let write = characteristic.write.unwrap();
+            assert!(write.write);
+            assert!(write.write_without_response);
+            assert!(!write.encrypt_write);
+            assert_eq!(
+                write.encrypt_authenticated_write,
+                uuid == CHARACTERISTIC_COMMAND_UUID
+            );
+        }
+
+        let write = write_no_response_characteristic(
+            handler_for(CHARACTERISTIC_TEXT_UUID).0,
+            Security::Encrypted,
+        )
+        .write
+        .unwrap();
+        assert!(write.encrypt_write);
+        assert!(!write.encrypt_authenticated_write);
+    }
+
+    #[tokio::test]
+    async fn long_write() {
+        let (handler, mut receiver) = handler();
+        let value = "Psalm 23, Café".as_bytes();
+        // é is split over the last two parts of the long write
+        for (offset, part) in [(0, &value[..7]), (7, &value[7..14]), (14, &value[14..])] {
+            handler.write(ADDRESS, offset, true, part).await.unwrap();
+        }
+        assert_eq!(
+            receiver.next().await,
+            Some(Message::Status("Psalm 23, Café".to_owned()))
+        );
+
+        assert!(matches!(
+            handler.write(ADDRESS, 3, true, b"x").await,
+            Err(ReqError::InvalidOffset)
+        ));
+    }
+
+    #[tokio::test]
+    async fn not_utf8() {
+        let (handler, mut receiver) = handler();
+        assert!(matches!(
+            handler.write(ADDRESS, 0, false, &[0xC3]).await,
+            Err(ReqError::Failed)
+        ));
+        handler.write(ADDRESS, 0, false, b"1/3").await.unwrap();
+        assert_eq!(
+            receiver.next().await,
+            Some(Message::Status("1/3".to_owned()))
+        );
+    }
+}
@@ -7,26 +7,49 @@ use bluer::{
     adv::{Advertisement, AdvertisementHandle},
     gatt::local::{Application, ApplicationHandle, Characteristic, Service},
 };
-use lipl_display_common::{BackgroundThread, Message};
+use lipl_display_common::{
+    BackgroundThread, Injector, Message, MessageSource, Security, access::Access,
+};
 
 use futures_channel::mpsc;
-use futures_util::{Stream, StreamExt};
+use futures_util::{FutureExt, SinkExt, Stream, StreamExt};
 use log::{error, trace};
 use pin_project::{pin_project, pinned_drop};
 use std::pin::Pin;
-use tokio::sync::Mutex;
 
 mod characteristic;
 mod error;
+mod screen;
 
 pub use error::Error;
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Bluetooth adapter, advertised name and allowed devices of the gatt peripheral
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Adapter name as hci0, the default adapter if none
+    pub adapter: Option<String>,
+    pub local_name: String,
+    /// Devices that may write, every device by default
+    pub access: Arc<Access>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            adapter: None,
+            local_name: lipl_display_common::LOCAL_NAME.to_owned(),
+            access: Arc::new(Access::default()),
+        }
+    }
+}
+
 #[pin_project(PinnedDrop)]
 pub struct MessageStream {
     values_tx: mpsc::Sender<Message>,
     #[pin]
     values_rx: mpsc::Receiver<Message>,
+    injector: Injector,
     adv_handle: Option<AdvertisementHandle>,
     app_handle: Option<ApplicationHandle>,
 }
@@ -56,6 +79,27 @@ impl Stream for MessageStream {
     }
 }
 
+impl MessageSource for MessageStream {
+    fn name(&self) -> &str {
+        "bluer"
+    }
+
+    fn stop(&mut self) -> futures_util::future::BoxFuture<'_, ()> {
+        self.values_rx.close();
+        if self.adv_handle.take().is_some() {
+            trace!("Handle dropped for Advertisement");
+        }
+        if self.app_handle.take().is_some() {
+            trace!("Handle dropped for Application");
+        }
+        futures_util::future::ready(()).boxed()
+    }
+
+    fn injector(&self) -> Option<Injector> {
+        Some(self.injector.clone())
+    }
+}
+
 /// Utility function so that dependent crates do not need tokio dependency
 pub fn create_runtime() -> Result<tokio::runtime::Runtime> {
     tokio::runtime::Builder::new_current_thread()
@@ -65,7 +109,7 @@ pub fn create_runtime() -> Result<tokio::runtime::Runtime> {
         .map_err(Error::Common)
 }
 
-async fn advertise(adapter: &bluer::Adapter) -> Result<AdvertisementHandle> {
+async fn advertise(adapter: &bluer::Adapter, local_name: &str) -> Result<AdvertisementHandle> {
     let mut manufacturer_data = BTreeMap::new();
     manufacturer_data.insert(
         lipl_display_common::MANUFACTURER_ID,
@@ -77,7 +121,7 @@ async fn advertise(adapter: &bluer::Adapter) -> Result<AdvertisementHandle> {
             .collect(),
         manufacturer_data,
         discoverable: Some(true),
-        local_name: Some(lipl_display_common::LOCAL_NAME.to_owned()),
+        local_name: Some(local_name.to_owned()),
         tx_power: Some(8),
 
         ..Default::default()
@@ -170,24 +214,59 @@ impl BackgroundThread for ListenBluer {
 }
 
 /// Used in flutter version
+///
+/// Devices allowed to write are read from `/etc/lipl.toml`, fails if it cannot be read.
 pub async fn listen_stream() -> Result<MessageStream> {
+    listen_stream_with(&Config {
+        access: Arc::new(Access::load()?),
+        ..Default::default()
+    })
+    .await
+}
+
+/// Start the gatt peripheral on the adapter and with the local name of config
+pub async fn listen_stream_with(config: &Config) -> Result<MessageStream> {
     let (values_tx, values_rx) = mpsc::channel::<Message>(100);
 
     let session = bluer::Session::new().await?;
-    let adapter = session.default_adapter().await?;
+    let adapter = match &config.adapter {
+        Some(name) => session.adapter(name)?,
+        None => session.default_adapter().await?,
+    };
     trace!("Bluetooth adapter {} found", adapter.name());
-    let adv_handle = advertise(&adapter).await?;
+    let adv_handle = advertise(&adapter, &config.local_name).await?;
     trace!("Advertising started");
     let uuid: Uuid = lipl_display_common::SERVICE_UUID;
     let primary: bool = true;
+    let screen = Arc::new(screen::ScreenState::default());
     let characteristics: Vec<Characteristic> = [
         lipl_display_common::CHARACTERISTIC_TEXT_UUID,
         lipl_display_common::CHARACTERISTIC_STATUS_UUID,
         lipl_display_common::CHARACTERISTIC_COMMAND_UUID,
+        lipl_display_common::CHARACTERISTIC_PLAYLIST_UUID,
     ]
     .into_iter()
-    .map(|c| (c, Arc::new(Mutex::new(vec![]))))
-    .map(|v| characteristic::write_no_response_characteristic(v.0, v.1, values_tx.clone()))
+    .map(|uuid| {
+        characteristic::write_no_response_characteristic(
+            characteristic::WriteHandler::new(
+                uuid,
+                screen.clone(),
+                config.access.clone(),
+                values_tx.clone(),
+            ),
+            Security::default_for(uuid),
+        )
+    })
+    .chain([
+        characteristic::read_notify_characteristic(
+            lipl_display_common::CHARACTERISTIC_SCREEN_UUID,
+            screen.clone(),
+        ),
+        characteristic::read_characteristic(
+            lipl_display_common::CHARACTERISTIC_INFO_UUID,
+            lipl_display_common::Info::default().to_json()?.into_bytes(),
+        ),
+    ])
     .collect();
 
     let app = Application {
@@ -202,9 +281,22 @@ pub async fn listen_stream() -> Result<MessageStream> {
 
     let app_handle = adapter.serve_gatt_application(app).await?;
 
+    // Injected messages update the screen characteristic like written ones
+    let (injector, mut injected) = mpsc::channel::<Message>(10);
+    let mut sender = values_tx.clone();
+    tokio::spawn(async move {
+        while let Some(message) = injected.next().await {
+            screen.update(message.clone()).await;
+            if sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
     Ok(MessageStream {
         values_tx,
         values_rx,
+        injector,
         adv_handle: Some(adv_handle),
         app_handle: Some(app_handle),
     })
@@ -0,0 +1,49 @@
+use bluer::gatt::local::CharacteristicNotifier;
+use lipl_display_common::{Command, HandleMessage, LiplScreen, Message};
+use log::{error, trace};
+use tokio::sync::Mutex;
+
+/// Screen as shown by the display, shared by the read and notify handlers
+pub struct ScreenState {
+    screen: Mutex<LiplScreen>,
+    notifiers: Mutex<Vec<CharacteristicNotifier>>,
+}
+
+impl Default for ScreenState {
+    fn default() -> Self {
+        let mut screen = LiplScreen::default();
+        screen.handle_message(Message::Command(Command::Wait));
+        Self {
+            screen: Mutex::new(screen),
+            notifiers: Mutex::new(vec![]),
+        }
+    }
+}
+
+impl ScreenState {
+    pub async fn json(&self) -> Vec<u8> {
+        self.screen
+            .lock()
+            .await
+            .to_json()
+            .map(String::into_bytes)
+            .unwrap_or_default()
+    }
+
+    pub async fn subscribe(&self, notifier: CharacteristicNotifier) {
+        trace!("Notification session started for screen");
+        self.notifiers.lock().await.push(notifier);
+    }
+
+    pub async fn update(&self, message: Message) {
+        self.screen.lock().await.handle_message(message);
+        let value = self.json().await;
+        let mut notifiers = self.notifiers.lock().await;
+        notifiers.retain(|notifier| !notifier.is_stopped());
+        for notifier in notifiers.iter_mut() {
+            if let Err(error) = notifier.notify(value.clone()).await {
+                error!("Cannot notify screen: {error}");
+            }
+        }
+    }
+}
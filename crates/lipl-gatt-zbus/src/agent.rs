@@ -0,0 +1,336 @@
+use futures::{SinkExt, StreamExt, channel::mpsc::Sender};
+use lipl_display_common::access::device_address;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use zbus::{Connection, interface, zvariant::ObjectPath};
+
+use crate::{gatt::BluezError, proxy::Device1Proxy};
+
+/// Object path of the pairing agent
+pub(crate) const AGENT_PATH: &str = "/org/bluez/app/agent";
+/// A display can show a passkey, but cannot confirm or enter one
+pub(crate) const CAPABILITY: &str = "DisplayOnly";
+/// Time after which the passkey is no longer shown if the device did not pair
+const PAIRING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Change of the status line while pairing
+#[derive(Debug, PartialEq)]
+pub(crate) enum Pairing {
+    /// Show the code to type on the device instead of the status
+    Code(String),
+    /// Show the status from before pairing again
+    Done,
+}
+
+/// Pairing agent for a display without keyboard
+///
+/// The passkey is shown as status, so it can be read off the screen and typed on the device that pairs.
+/// The previous status returns when the device is paired, pairing is canceled or times out.
+/// Only the last pairing is followed, a new passkey replaces the one shown.
+pub(crate) struct Agent {
+    sender: Sender<Pairing>,
+    /// Task following the pairing of the passkey shown
+    following: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Agent {
+    pub fn new(sender: Sender<Pairing>) -> Self {
+        Self {
+            sender,
+            following: Mutex::new(None),
+        }
+    }
+
+    /// Stop following the previous pairing, replaced by task if any
+    fn follow(&self, task: Option<JoinHandle<()>>) {
+        let mut following = self
+            .following
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        if let Some(previous) = std::mem::replace(&mut *following, task) {
+            previous.abort();
+        }
+    }
+
+    async fn send(&self, pairing: Pairing) {
+        if let Err(error) = self.sender.clone().send(pairing).await {
+            tracing::error!("Cannot show pairing status: {error}");
+        }
+    }
+
+    /// Show code until device is paired
+    async fn show(&self, connection: &Connection, device: ObjectPath<'_>, code: String) {
+        tracing::info!("Pairing with {}", address(&device));
+        self.follow(None);
+        self.send(Pairing::Code(code)).await;
+        let connection = connection.clone();
+        let device = device.into_owned();
+        let mut sender = self.sender.clone();
+        let task = tokio::spawn(async move {
+            let followed = tokio::time::timeout(PAIRING_TIMEOUT, async {
+                match paired(&connection, &device).await {
+                    Ok(()) => tracing::info!("Paired with {}", address(&device)),
+                    Err(error) => {
+                        // The passkey stays until bluez cancels or the timeout
+                        tracing::warn!("Cannot follow pairing with {}: {error}", address(&device));
+                        std::future::pending::<()>().await;
+                    }
+                }
+            });
+            if followed.await.is_err() {
+                tracing::info!("Pairing with {} timed out", address(&device));
+            }
+            sender.send(Pairing::Done).await.ok();
+        });
+        self.follow(Some(task));
+    }
+}
+
+/// Wait until the Paired property of device is true
+async fn paired(connection: &Connection, device: &ObjectPath<'_>) -> zbus::Result<()> {
+    let proxy = Device1Proxy::builder(connection)
+        .path(device)?
+        .build()
+        .await?;
+    let mut changes = proxy.receive_paired_changed().await;
+    if proxy.paired().await? {
+        return Ok(());
+    }
+    while let Some(change) = changes.next().await {
+        if change.get().await? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn address(device: &ObjectPath<'_>) -> String {
+    device_address(device.as_str()).unwrap_or_else(|| device.to_string())
+}
+
+#[interface(name = "org.bluez.Agent1")]
+impl Agent {
+    fn release(&self) {
+        tracing::info!("Agent released by bluez");
+    }
+
+    fn request_pin_code(&self, device: ObjectPath<'_>) -> Result<String, BluezError> {
+        Err(BluezError::Rejected(format!(
+            "No pin code can be entered for {}",
+            address(&device)
+        )))
+    }
+
+    async fn display_pin_code(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        device: ObjectPath<'_>,
+        pincode: &str,
+    ) {
+        self.show(connection, device, format!("Koppelcode {pincode}"))
+            .await;
+    }
+
+    fn request_passkey(&self, device: ObjectPath<'_>) -> Result<u32, BluezError> {
+        Err(BluezError::Rejected(format!(
+            "No passkey can be entered for {}",
+            address(&device)
+        )))
+    }
+
+    async fn display_passkey(
+        &self,
+        #[zbus(connection)] connection: &Connection,
+        device: ObjectPath<'_>,
+        passkey: u32,
+        entered: u16,
+    ) {
+        if entered == 0 {
+            self.show(connection, device, format!("Koppelcode {passkey:06}"))
+                .await;
+        }
+    }
+
+    fn request_confirmation(
+        &self,
+        device: ObjectPath<'_>,
+        _passkey: u32,
+    ) -> Result<(), BluezError> {
+        Err(BluezError::Rejected(format!(
+            "Pairing with {} cannot be confirmed",
+            address(&device)
+        )))
+    }
+
+    fn request_authorization(&self, device: ObjectPath<'_>) -> Result<(), BluezError> {
+        Err(BluezError::Rejected(format!(
+            "Pairing with {} cannot be authorized",
+            address(&device)
+        )))
+    }
+
+    fn authorize_service(&self, device: ObjectPath<'_>, uuid: &str) {
+        tracing::info!("Service {uuid} authorized for {}", address(&device));
+    }
+
+    async fn cancel(&self) {
+        tracing::info!("Pairing canceled");
+        self.follow(None);
+        self.send(Pairing::Done).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use std::time::Duration;
+    use zbus::{Guid, conn::Builder, interface, proxy, zvariant::ObjectPath};
+
+    use super::{AGENT_PATH, Agent, Pairing};
+
+    const DEVICE_PATH: &str = "/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF";
+    const OTHER_DEVICE_PATH: &str = "/org/bluez/hci0/dev_11_22_33_44_55_66";
+
+    #[proxy(
+        interface = "org.bluez.Agent1",
+        default_service = "org.bluez",
+        default_path = "/org/bluez/app/agent"
+    )]
+    trait Agent1 {
+        fn display_passkey(
+            &self,
+            device: &ObjectPath<'_>,
+            passkey: u32,
+            entered: u16,
+        ) -> zbus::Result<()>;
+        fn request_passkey(&self, device: &ObjectPath<'_>) -> zbus::Result<u32>;
+        fn request_confirmation(&self, device: &ObjectPath<'_>, passkey: u32) -> zbus::Result<()>;
+        fn cancel(&self) -> zbus::Result<()>;
+    }
+
+    /// Device of bluez that pairs
+    struct Device {
+        paired: bool,
+    }
+
+    #[interface(name = "org.bluez.Device1")]
+    impl Device {
+        #[zbus(property)]
+        fn paired(&self) -> bool {
+            self.paired
+        }
+    }
+
+    #[tokio::test]
+    async fn passkey_as_status() {
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair().unwrap();
+        let (sender, mut receiver) = futures::channel::mpsc::channel(10);
+        let (server, client) = futures::join!(
+            Builder::unix_stream(server_stream)
+                .server(Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at(AGENT_PATH, Agent::new(sender))
+                .unwrap()
+                .build(),
+            Builder::unix_stream(client_stream)
+                .p2p()
+                .serve_at(DEVICE_PATH, Device { paired: false })
+                .unwrap()
+                .build(),
+        );
+        let (_server, client) = (server.unwrap(), client.unwrap());
+        let proxy = Agent1Proxy::new(&client).await.unwrap();
+        let device = ObjectPath::try_from(DEVICE_PATH).unwrap();
+
+        proxy.display_passkey(&device, 1234, 0).await.unwrap();
+        assert_eq!(
+            receiver.next().await,
+            Some(Pairing::Code("Koppelcode 001234".to_owned()))
+        );
+
+        let error = proxy.request_confirmation(&device, 1234).await.unwrap_err();
+        assert!(error.to_string().contains("org.bluez.Error.Rejected"));
+        assert!(proxy.request_passkey(&device).await.is_err());
+
+        let interface = client
+            .object_server()
+            .interface::<_, Device>(DEVICE_PATH)
+            .await
+            .unwrap();
+        interface.get_mut().await.paired = true;
+        interface
+            .get()
+            .await
+            .paired_changed(interface.signal_emitter())
+            .await
+            .unwrap();
+        assert_eq!(receiver.next().await, Some(Pairing::Done));
+
+        proxy.cancel().await.unwrap();
+        assert_eq!(receiver.next().await, Some(Pairing::Done));
+    }
+
+    #[tokio::test]
+    async fn overlapping_pairings() {
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair().unwrap();
+        let (sender, mut receiver) = futures::channel::mpsc::channel(10);
+        let (server, client) = futures::join!(
+            Builder::unix_stream(server_stream)
+                .server(Guid::generate())
+                .unwrap()
+                .p2p()
+                .serve_at(AGENT_PATH, Agent::new(sender))
+                .unwrap()
+                .build(),
+            Builder::unix_stream(client_stream)
+                .p2p()
+                .serve_at(DEVICE_PATH, Device { paired: false })
+                .unwrap()
+                .serve_at(OTHER_DEVICE_PATH, Device { paired: false })
+                .unwrap()
+                .build(),
+        );
+        let (_server, client) = (server.unwrap(), client.unwrap());
+        let proxy = Agent1Proxy::new(&client).await.unwrap();
+        let pair = |path: &'static str| {
+            let client = client.clone();
+            async move {
+                let interface = client
+                    .object_server()
+                    .interface::<_, Device>(path)
+                    .await
+                    .unwrap();
+                interface.get_mut().await.paired = true;
+                interface
+                    .get()
+                    .await
+                    .paired_changed(interface.signal_emitter())
+                    .await
+                    .unwrap();
+            }
+        };
+
+        for (path, passkey) in [(DEVICE_PATH, 1234), (OTHER_DEVICE_PATH, 5678)] {
+            let device = ObjectPath::try_from(path).unwrap();
+            proxy.display_passkey(&device, passkey, 0).await.unwrap();
+        }
+        assert_eq!(
+            receiver.next().await,
+            Some(Pairing::Code("Koppelcode 001234".to_owned()))
+        );
+        assert_eq!(
+            receiver.next().await,
+            Some(Pairing::Code("Koppelcode 005678".to_owned()))
+        );
+
+        // The first pairing is no longer followed, its code was replaced
+        pair(DEVICE_PATH).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(receiver.try_recv().is_err());
+
+        pair(OTHER_DEVICE_PATH).await;
+        assert_eq!(receiver.next().await, Some(Pairing::Done));
+    }
+}
@@ -0,0 +1,209 @@
+use futures::{SinkExt, channel::mpsc::Sender};
+use lipl_display_common::{Error, LiplScreen, Message, access::Access};
+use std::sync::Arc;
+use zbus::{Connection, conn::Builder, interface, object_server::SignalEmitter, proxy};
+
+use crate::Result;
+
+/// Well-known name of the display on the session bus
+pub const DISPLAY1_NAME: &str = "nl.paulmin.Lipl.Display1";
+/// Object path of the display interface
+pub const DISPLAY1_PATH: &str = "/nl/paulmin/Lipl/Display1";
+
+/// Current screen for desktop tooling, scripts and tests
+///
+/// Messages sent with `Send` are handled as if received over gatt,
+/// after [`Access::accept_local`] refused exit, poweroff and messages that have to be signed.
+pub struct Display1 {
+    screen: LiplScreen,
+    sender: Sender<Message>,
+    access: Arc<Access>,
+}
+
+#[interface(name = "nl.paulmin.Lipl.Display1")]
+impl Display1 {
+    /// Handle a json serialized message, for example `{"part":"Hello"}`
+    async fn send(&self, message_json: &str) -> zbus::fdo::Result<()> {
+        let message = serde_json::from_str::<Message>(message_json)
+            .map_err(|error| zbus::fdo::Error::InvalidArgs(error.to_string()))?;
+        let message = self.access.accept_local(message).map_err(|error| {
+            let count = self.access.count_rejection(&error);
+            tracing::warn!(
+                "Message sent on the session bus rejected, {count} times so far: {error}"
+            );
+            match error {
+                Error::TooLong { .. } => zbus::fdo::Error::InvalidArgs(error.to_string()),
+                Error::RateLimited => zbus::fdo::Error::LimitsExceeded(error.to_string()),
+                _ => zbus::fdo::Error::AccessDenied(error.to_string()),
+            }
+        })?;
+        self.sender
+            .clone()
+            .send(message)
+            .await
+            .map_err(|error| zbus::fdo::Error::Failed(error.to_string()))
+    }
+
+    /// Let the next device to write claim the display
+    fn reset_claim(&self) {
+        if let Some(address) = self.access.claimed() {
+            tracing::info!("Claim of {address} reset");
+        }
+        self.access.reset_claim();
+    }
+
+    #[zbus(property)]
+    fn text(&self) -> String {
+        self.screen.text.clone()
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> String {
+        self.screen.status.clone()
+    }
+
+    #[zbus(property)]
+    fn dark(&self) -> bool {
+        self.screen.dark
+    }
+
+    #[zbus(property)]
+    fn font_size(&self) -> f64 {
+        self.screen.font_size.into()
+    }
+
+    /// Json serialized message received by the display
+    #[zbus(signal)]
+    async fn message_received(emitter: &SignalEmitter<'_>, message_json: &str) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "nl.paulmin.Lipl.Display1",
+    default_service = "nl.paulmin.Lipl.Display1",
+    default_path = "/nl/paulmin/Lipl/Display1"
+)]
+pub trait Display1 {
+    fn send(&self, message_json: &str) -> zbus::Result<()>;
+
+    fn reset_claim(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn text(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn status(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn dark(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn font_size(&self) -> zbus::Result<f64>;
+
+    #[zbus(signal)]
+    fn message_received(&self, message_json: String) -> zbus::Result<()>;
+}
+
+/// Serve the display interface on the connection to build
+pub async fn serve(
+    builder: Builder<'_>,
+    sender: Sender<Message>,
+    access: Arc<Access>,
+) -> Result<Connection> {
+    let display = Display1 {
+        screen: LiplScreen::default(),
+        sender,
+        access,
+    };
+    Ok(builder.serve_at(DISPLAY1_PATH, display)?.build().await?)
+}
+
+/// Publish the screen after message changed it
+pub async fn update(connection: &Connection, screen: &LiplScreen, message: &Message) -> Result<()> {
+    let interface = connection
+        .object_server()
+        .interface::<_, Display1>(DISPLAY1_PATH)
+        .await?;
+    let emitter = interface.signal_emitter();
+    let mut display = interface.get_mut().await;
+    let previous = std::mem::replace(&mut display.screen, screen.clone());
+    if previous.text != screen.text {
+        display.text_changed(emitter).await?;
+    }
+    if previous.status != screen.status {
+        display.status_changed(emitter).await?;
+    }
+    if previous.dark != screen.dark {
+        display.dark_changed(emitter).await?;
+    }
+    if previous.font_size != screen.font_size {
+        display.font_size_changed(emitter).await?;
+    }
+    Display1::message_received(emitter, &serde_json::to_string(message)?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use lipl_display_common::{
+        HandleMessage, LiplScreen, Message,
+        access::{Access, AccessConfig},
+    };
+    use std::sync::Arc;
+    use zbus::{Guid, conn::Builder, proxy::CacheProperties};
+
+    use super::{Display1Proxy, serve, update};
+
+    #[tokio::test]
+    async fn private_bus() {
+        let (server_stream, client_stream) = tokio::net::UnixStream::pair().unwrap();
+        let (sender, mut receiver) = futures::channel::mpsc::channel(10);
+        let guid = Guid::generate();
+        let access = Arc::new(Access::new(AccessConfig {
+            claim: true,
+            ..Default::default()
+        }));
+        assert!(access.authorize(Some("AA:BB:CC:DD:EE:FF")));
+        let (server, client) = futures::join!(
+            serve(
+                Builder::unix_stream(server_stream)
+                    .server(guid)
+                    .unwrap()
+                    .p2p(),
+                sender,
+                access.clone(),
+            ),
+            Builder::unix_stream(client_stream).p2p().build(),
+        );
+        let (server, client) = (server.unwrap(), client.unwrap());
+        let proxy = Display1Proxy::builder(&client)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        proxy.send(r#"{"part":"Amazing grace"}"#).await.unwrap();
+        assert_eq!(
+            receiver.next().await,
+            Some(Message::Part("Amazing grace".into()))
+        );
+        assert!(proxy.send("Amazing grace").await.is_err());
+        let error = proxy.send(r#"{"command":"Poweroff"}"#).await.unwrap_err();
+        assert!(error.to_string().contains("AccessDenied"));
+
+        proxy.reset_claim().await.unwrap();
+        assert_eq!(access.claimed(), None);
+
+        let mut signals = proxy.receive_message_received().await.unwrap();
+        let message = Message::Status("1/3".to_owned());
+        let mut screen = LiplScreen::new(true, 30.0);
+        screen.handle_message(message.clone());
+        update(&server, &screen, &message).await.unwrap();
+
+        let signal = signals.next().await.unwrap();
+        assert_eq!(signal.args().unwrap().message_json, r#"{"status":"1/3"}"#);
+        assert_eq!(proxy.status().await.unwrap(), "1/3");
+        assert!(proxy.dark().await.unwrap());
+        assert_eq!(proxy.font_size().await.unwrap(), 30.0);
+    }
+}
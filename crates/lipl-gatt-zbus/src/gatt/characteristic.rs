@@ -1,11 +1,14 @@
-use futures::StreamExt;
 use futures::channel::mpsc::{Sender, channel};
+use futures::channel::oneshot;
+use futures::{SinkExt, StreamExt};
 use std::collections::HashMap;
 
 use crate::{GattCharacteristicConfig, object_path_extensions::OwnedObjectPathExtensions};
+use lipl_display_common::Security;
 use uuid::Uuid;
 use zbus::{
     interface,
+    object_server::SignalEmitter,
     zvariant::{OwnedObjectPath, Value},
 };
 
@@ -15,8 +18,10 @@ pub struct Characteristic {
     pub uuid: Uuid,
     pub read: bool,
     pub write: bool,
-    #[allow(dead_code)]
     pub notify: bool,
+    pub notifying: bool,
+    pub security: Security,
+    pub value: Vec<u8>,
     pub service_path: String,
     pub descriptor_paths: Vec<String>,
     pub sender: Sender<Request>,
@@ -43,16 +48,45 @@ impl
     ) -> Self {
         Characteristic::new(
             format!("{}/char{}", gatt_char_config.2, gatt_char_config.0 + 1),
-            gatt_char_config.1.uuid,
+            gatt_char_config.1,
             gatt_char_config.2.clone(),
             gatt_char_config.3.clone(),
-            gatt_char_config.1.read,
-            gatt_char_config.1.write,
             gatt_char_config.4,
         )
     }
 }
 
+/// Errors replied to bluez
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "org.bluez.Error")]
+pub enum BluezError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    NotAuthorized(String),
+    NotSupported(String),
+    Failed(String),
+    Rejected(String),
+    Canceled(String),
+    InvalidOffset(String),
+    InvalidValueLength(String),
+    NotPermitted(String),
+}
+
+impl From<lipl_display_common::Error> for BluezError {
+    fn from(error: lipl_display_common::Error) -> Self {
+        use lipl_display_common::Error;
+        match error {
+            Error::InvalidOffset(_) => Self::InvalidOffset(error.to_string()),
+            Error::TooLong { .. } => Self::InvalidValueLength(error.to_string()),
+            Error::RateLimited => Self::NotPermitted(error.to_string()),
+            Error::Unsigned | Error::Signature | Error::Replayed(_) => {
+                Self::NotAuthorized(error.to_string())
+            }
+            _ => Self::Failed(error.to_string()),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct WriteRequest {
     pub uuid: Uuid,
@@ -60,19 +94,18 @@ pub struct WriteRequest {
     pub mtu: Option<u16>,
     pub device: Option<String>,
     pub offset: Option<u16>,
-    #[allow(dead_code)]
     pub write_type: Option<String>,
     pub service_uuid: Uuid,
+    /// Whether the write is accepted
+    pub reply: Option<oneshot::Sender<Result<(), BluezError>>>,
 }
 
 #[derive(Debug)]
 pub struct ReadRequest {
-    #[allow(dead_code)]
     pub uuid: Uuid,
     pub mtu: Option<u16>,
     pub device: Option<String>,
     pub offset: Option<u16>,
-    #[allow(dead_code)]
     pub sender: Option<Sender<Vec<u8>>>,
     #[allow(dead_code)]
     pub service_uuid: Uuid,
@@ -103,6 +136,7 @@ impl From<(Uuid, Vec<u8>, &HashMap<String, Value<'_>>, Uuid)> for WriteRequest {
             offset: option_convert!(options.2, "offset", u16, Value::U16, clone),
             write_type: option_convert!(options.2, "type", String, Value::Str, to_string),
             service_uuid: options.3,
+            reply: None,
         }
     }
 }
@@ -183,19 +217,20 @@ impl std::fmt::Display for Request {
 impl Characteristic {
     pub fn new(
         object_path: String,
-        uuid: Uuid,
+        config: &GattCharacteristicConfig,
         service_path: String,
         sender: Sender<Request>,
-        read: bool,
-        write: bool,
         service_uuid: Uuid,
     ) -> Self {
         Self {
             object_path,
-            uuid,
-            read,
-            write,
-            notify: false,
+            uuid: config.uuid,
+            read: config.read,
+            write: config.write,
+            notify: config.notify,
+            notifying: false,
+            security: config.security,
+            value: vec![],
             service_path,
             descriptor_paths: vec![],
             sender,
@@ -220,15 +255,30 @@ impl Characteristic {
         let mut flags = vec![];
         if self.read {
             flags.push("read".into());
-            // flags.push("encrypt-authenticated-read".into());
+            flags.extend(self.security.read_flag().map(String::from));
         }
         if self.write {
+            // With write, rejected writes are answered with the error, as with bluer
+            flags.push("write".into());
             flags.push("write-without-response".into());
-            // flags.push("encrypt-authenticated-write".to_owned());
+            flags.extend(self.security.write_flag().map(String::from));
+        }
+        if self.notify {
+            flags.push("notify".into());
         }
         flags
     }
 
+    #[zbus(property)]
+    fn value(&self) -> Vec<u8> {
+        self.value.clone()
+    }
+
+    #[zbus(property)]
+    fn notifying(&self) -> bool {
+        self.notifying
+    }
+
     #[zbus(property)]
     fn service(&self) -> OwnedObjectPath {
         self.service_path.to_owned_object_path()
@@ -262,20 +312,108 @@ impl Characteristic {
     }
 
     #[zbus(name = "WriteValue")]
-    fn write_value(
+    async fn write_value(
         &mut self,
         value: Vec<u8>,
-        options: HashMap<String, Value>,
-    ) -> zbus::fdo::Result<()> {
+        options: HashMap<String, Value<'_>>,
+    ) -> Result<(), BluezError> {
         if !self.write {
+            return Err(BluezError::NotSupported("Write not supported".into()));
+        }
+        let (reply, accepted) = oneshot::channel::<Result<(), BluezError>>();
+        let mut write_request: WriteRequest =
+            (self.uuid, value, &options, self.service_uuid).into();
+        tracing::info!("Write request {:?} {:?}", write_request, self.service_uuid);
+        write_request.reply = Some(reply);
+        // Waits while earlier writes are handled, instead of failing
+        self.sender
+            .send(Request::Write(write_request))
+            .await
+            .map_err(|e| BluezError::Failed(e.to_string()))?;
+        accepted
+            .await
+            .unwrap_or_else(|_| Err(BluezError::Failed("Write request not handled".into())))
+    }
+
+    #[zbus(name = "StartNotify")]
+    async fn start_notify(
+        &mut self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        if !self.notify {
             return Err(zbus::fdo::Error::NotSupported(
                 "org.bluez.Error.NotSupported".into(),
             ));
         }
-        let write_request: WriteRequest = (self.uuid, value, &options, self.service_uuid).into();
-        tracing::info!("Write request {:?} {:?}", write_request, self.service_uuid);
-        self.sender
-            .try_send(Request::Write(write_request))
-            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+        self.notifying = true;
+        tracing::info!("Notifications started for {}", self.uuid);
+        self.notifying_changed(&emitter).await?;
+        Ok(())
+    }
+
+    #[zbus(name = "StopNotify")]
+    async fn stop_notify(
+        &mut self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        self.notifying = false;
+        tracing::info!("Notifications stopped for {}", self.uuid);
+        self.notifying_changed(&emitter).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Characteristic;
+    use crate::gatt_application::GattCharacteristicConfigBuilder;
+    use futures::channel::mpsc::channel;
+    use lipl_display_common::{CHARACTERISTIC_COMMAND_UUID, SERVICE_UUID, Security};
+
+    fn characteristic(read: bool, write: bool, security: Security) -> Characteristic {
+        let config = GattCharacteristicConfigBuilder::default()
+            .uuid(CHARACTERISTIC_COMMAND_UUID)
+            .read(read)
+            .write(write)
+            .security(security)
+            .build()
+            .unwrap();
+        Characteristic::new(
+            "/org/bluez/app/service1/char1".into(),
+            &config,
+            "/org/bluez/app/service1".into(),
+            channel(1).0,
+            SERVICE_UUID,
+        )
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(
+            characteristic(false, true, Security::Open).flags(),
+            vec!["write", "write-without-response"]
+        );
+        assert_eq!(
+            characteristic(false, true, Security::Authenticated).flags(),
+            vec![
+                "write",
+                "write-without-response",
+                "encrypt-authenticated-write"
+            ]
+        );
+        assert_eq!(
+            characteristic(true, false, Security::Encrypted).flags(),
+            vec!["read", "encrypt-read"]
+        );
+        assert_eq!(
+            characteristic(true, true, Security::Authenticated).flags(),
+            vec![
+                "read",
+                "encrypt-authenticated-read",
+                "write",
+                "write-without-response",
+                "encrypt-authenticated-write"
+            ]
+        );
     }
 }
@@ -3,5 +3,5 @@ mod characteristic;
 mod service;
 
 pub use application::Application;
-pub use characteristic::{Characteristic, Request, WriteRequest};
+pub use characteristic::{BluezError, Characteristic, ReadRequest, Request, WriteRequest};
 pub use service::Service;
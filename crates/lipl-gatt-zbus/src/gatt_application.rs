@@ -1,6 +1,7 @@
 use crate::gatt::{Characteristic, Request, Service};
 use derive_builder::Builder;
 use futures::channel::mpsc::Sender;
+use lipl_display_common::Security;
 use uuid::Uuid;
 
 #[derive(Clone, Debug)]
@@ -26,6 +27,11 @@ pub struct GattCharacteristicConfig {
     pub read: bool,
     #[builder(default = "true")]
     pub write: bool,
+    #[builder(default = "false")]
+    pub notify: bool,
+    /// Security required for reading and writing
+    #[builder(default)]
+    pub security: Security,
 }
 
 #[derive(Builder, Clone, Debug, Default)]
@@ -1,26 +1,43 @@
+use agent::{Agent, Pairing};
+pub use display::{DISPLAY1_NAME, DISPLAY1_PATH, Display1, Display1Proxy};
 pub use error::Result;
 use futures::{
     FutureExt, SinkExt, Stream, StreamExt, TryFutureExt,
     channel::mpsc::{Receiver, Sender},
+    future::{BoxFuture, Fuse},
     select,
 };
 use gatt::Request;
 use gatt_application::GattCharacteristicConfig;
-use lipl_display_common::{Command, Message};
-use message_handler::{characteristics_map, handle_write_request};
+use lipl_display_common::{
+    CHARACTERISTIC_SCREEN_UUID, Command, HandleMessage, Info, Injector, LiplScreen, Message,
+    MessageSource, SERVICE_UUID,
+    access::Access,
+    framing::{LONG_WRITE_DELAY, Reassembler},
+};
+use message_handler::{
+    LongWrite, characteristics_map, execute_long_write, handle_read_request, handle_write_request,
+    is_long_write,
+};
+use notifier::Notifier;
 use peripheral::Peripheral;
 use pin_project::pin_project;
 use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
-use zbus::{names::OwnedInterfaceName, zvariant::OwnedValue};
+use uuid::Uuid;
+use zbus::{Connection, names::OwnedInterfaceName, zvariant::OwnedValue};
 
 mod advertisement;
+mod agent;
 mod connection_extension;
+mod display;
 mod error;
 mod gatt;
 mod gatt_application;
 mod message_handler;
+mod notifier;
 mod object_path_extensions;
 mod peripheral;
 mod proxy;
@@ -32,7 +49,9 @@ pub struct GattListener {
     task: tokio::task::JoinHandle<()>,
     #[pin]
     receiver: futures::channel::mpsc::Receiver<Message>,
-    terminate: futures::channel::oneshot::Sender<()>,
+    terminate: Option<futures::channel::oneshot::Sender<()>>,
+    /// Messages handled as if written, also used by [`Display1`] `Send`
+    injector: Injector,
 }
 
 impl Stream for GattListener {
@@ -52,28 +71,87 @@ impl IntoFuture for GattListener {
     type Output = Result<()>;
     type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'static>>;
 
-    fn into_future(self) -> Self::IntoFuture {
-        if !self.task.is_finished() {
-            self.terminate.send(()).ok();
-        }
+    fn into_future(mut self) -> Self::IntoFuture {
+        self.terminate();
         self.task.err_into().boxed()
     }
 }
 
+impl MessageSource for GattListener {
+    fn name(&self) -> &str {
+        "zbus"
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, ()> {
+        self.terminate();
+        async move {
+            if let Err(error) = (&mut self.task).await {
+                tracing::error!("Error waiting for gatt listener to finish: {}", error);
+            }
+        }
+        .boxed()
+    }
+
+    fn injector(&self) -> Option<Injector> {
+        Some(self.injector.clone())
+    }
+}
+
 impl GattListener {
     pub fn new() -> Self {
         let (sender, receiver) = futures::channel::mpsc::channel::<Message>(100);
         let (terminate, terminate_receiver) = futures::channel::oneshot::channel::<()>();
+        let (injector, injected) = futures::channel::mpsc::channel::<Message>(10);
+        let injected_sender = injector.clone();
         Self {
             task: tokio::runtime::Handle::current().spawn(async move {
+                let access = match Access::load() {
+                    Ok(access) => Arc::new(access),
+                    Err(error) => {
+                        tracing::error!("Not starting, cannot read access config: {error}");
+                        return;
+                    }
+                };
+                let (pairing_sender, pairing) = futures::channel::mpsc::channel::<Pairing>(10);
+                let agent = Agent::new(pairing_sender);
+                let display = match zbus::conn::Builder::session()
+                    .and_then(|builder| builder.name(DISPLAY1_NAME))
+                {
+                    Ok(builder) => display::serve(builder, injected_sender, access.clone())
+                        .await
+                        .inspect_err(|error| {
+                            tracing::warn!("No {DISPLAY1_NAME} on the session bus: {error}")
+                        })
+                        .ok(),
+                    Err(error) => {
+                        tracing::warn!("No {DISPLAY1_NAME} on the session bus: {error}");
+                        None
+                    }
+                };
                 match Peripheral::new()
-                    .and_then(|bluez| {
-                        bluez.run(message_handler::gatt_application_config().unwrap())
+                    .and_then(|bluez| async move {
+                        if let Err(error) = bluez.register_agent(agent).await {
+                            tracing::warn!("No agent showing the passkey when pairing: {error}");
+                        }
+                        bluez
+                            .run(message_handler::gatt_application_config().unwrap())
+                            .await
                     })
                     .await
                 {
-                    Ok((rx, dispose)) => {
-                        handle_messages(rx, sender, terminate_receiver, dispose).await;
+                    Ok((rx, notifier, dispose)) => {
+                        handle_messages(
+                            rx,
+                            injected,
+                            pairing,
+                            sender,
+                            notifier,
+                            display,
+                            access,
+                            terminate_receiver,
+                            dispose,
+                        )
+                        .await;
                     }
                     Err(error) => {
                         tracing::error!("Error initializing Bluetooth: {}", error);
@@ -81,14 +159,85 @@ impl GattListener {
                 }
             }),
             receiver,
-            terminate,
+            terminate: Some(terminate),
+            injector,
+        }
+    }
+
+    fn terminate(&mut self) {
+        if let Some(terminate) = self.terminate.take()
+            && !self.task.is_finished()
+        {
+            terminate.send(()).ok();
         }
     }
 }
 
+async fn notify_screen(notifier: &Notifier, screen: &LiplScreen) {
+    match screen.to_json() {
+        Ok(json) => {
+            if let Err(error) = notifier
+                .notify(SERVICE_UUID, CHARACTERISTIC_SCREEN_UUID, json.into_bytes())
+                .await
+            {
+                tracing::error!("Error notifying screen: {}", error);
+            }
+        }
+        Err(error) => {
+            tracing::error!("Error serializing screen: {}", error);
+        }
+    }
+}
+
+/// Update the screen and pass the message on, returns false if the listener should stop
+async fn handle_message(
+    message: Message,
+    screen: &mut LiplScreen,
+    sender: &mut Sender<Message>,
+    notifier: &Notifier,
+    display: Option<&Connection>,
+) -> bool {
+    tracing::info!("Received message: {:?}", message);
+    screen.handle_message(message.clone());
+    notify_screen(notifier, screen).await;
+    if let Some(connection) = display
+        && let Err(error) = display::update(connection, screen, &message).await
+    {
+        tracing::error!("Error updating {DISPLAY1_NAME}: {}", error);
+    }
+    if [
+        Message::Command(Command::Exit),
+        Message::Command(Command::Poweroff),
+    ]
+    .contains(&message)
+    {
+        return false;
+    }
+    sender.send(message).await.unwrap();
+    true
+}
+
+/// Message from the long write, there is no request left to reply an error to
+fn execute(
+    long_write: &LongWrite,
+    map: &mut HashMap<(Uuid, Uuid), Reassembler>,
+    access: &Access,
+) -> Option<Message> {
+    execute_long_write(long_write, map, access)
+        .inspect_err(|error| tracing::error!("Cannot execute long write: {error:?}"))
+        .ok()
+        .flatten()
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn handle_messages(
     mut rx: Receiver<Request>,
+    mut injected: Receiver<Message>,
+    mut pairing: Receiver<Pairing>,
     mut sender: Sender<Message>,
+    notifier: Notifier,
+    display: Option<Connection>,
+    access: Arc<Access>,
     mut terminate_receiver: futures::channel::oneshot::Receiver<()>,
     dispose: Pin<Box<dyn Future<Output = Result<()>> + Send>>,
 ) {
@@ -96,23 +245,46 @@ async fn handle_messages(
     tracing::info!("Press <Ctr-C> or send signal SIGINT to end service");
 
     let mut map = characteristics_map();
+    let info = Info::default();
+    let mut screen = LiplScreen::default();
+    screen.handle_message(Message::Command(Command::Wait));
+    notify_screen(&notifier, &screen).await;
+    // Status shown before the pairing code
+    let mut before_pairing = None;
+    // Long write executed when no part follows in time
+    let mut long_write: Option<LongWrite> = None;
+    let mut long_write_timer = Box::pin(Fuse::terminated());
 
     loop {
         select! {
             request = rx.next() => {
                 match request {
                     Some(Request::Write(mut write_request)) => {
-                        if let Some(message) = handle_write_request(&mut write_request, &mut map) {
-                            tracing::info!("Received message: {:?}", message);
-                            if [Message::Command(Command::Exit), Message::Command(Command::Poweroff)].contains(&message)
-                            {
-                                break;
-                            }
-                            sender.send(message).await.unwrap();
+                        let key = (write_request.service_uuid, write_request.uuid);
+                        // A part for another characteristic follows the last part
+                        if let Some(pending) = long_write.take_if(|(pending, _)| *pending != key)
+                            && let Some(message) = execute(&pending, &mut map, &access)
+                            && !handle_message(message, &mut screen, &mut sender, &notifier, display.as_ref()).await
+                        {
+                            break;
+                        }
+                        let result = handle_write_request(&write_request, &mut map, &access);
+                        if is_long_write(&write_request) && result.is_ok() {
+                            long_write = Some((key, write_request.device.clone()));
+                            long_write_timer.set(tokio::time::sleep(LONG_WRITE_DELAY).fuse());
+                        }
+                        let message = result.as_ref().ok().cloned().flatten();
+                        if let Some(reply) = write_request.reply.take() {
+                            reply.send(result.map(|_| ())).ok();
+                        }
+                        if let Some(message) = message
+                            && !handle_message(message, &mut screen, &mut sender, &notifier, display.as_ref()).await
+                        {
+                            break;
                         }
                     }
-                    Some(Request::Read(_)) => {
-                        todo!("Implement read request handling");
+                    Some(Request::Read(read_request)) => {
+                        handle_read_request(read_request, &screen, &info);
                     }
                     None => {
                         tracing::info!("No more requests");
@@ -120,6 +292,33 @@ async fn handle_messages(
                     }
                 }
             },
+            message = injected.next() => {
+                if let Some(message) = message
+                    && !handle_message(message, &mut screen, &mut sender, &notifier, display.as_ref()).await
+                {
+                    break;
+                }
+            },
+            _ = long_write_timer => {
+                if let Some(pending) = long_write.take()
+                    && let Some(message) = execute(&pending, &mut map, &access)
+                    && !handle_message(message, &mut screen, &mut sender, &notifier, display.as_ref()).await
+                {
+                    break;
+                }
+            },
+            pairing = pairing.select_next_some() => {
+                let status = match pairing {
+                    Pairing::Code(code) => {
+                        before_pairing.get_or_insert_with(|| screen.status.clone());
+                        Some(code)
+                    }
+                    Pairing::Done => before_pairing.take(),
+                };
+                if let Some(status) = status {
+                    handle_message(Message::Status(status), &mut screen, &mut sender, &notifier, display.as_ref()).await;
+                }
+            },
             _ = terminate_receiver => break,
         }
     }
@@ -1,16 +1,18 @@
 use crate::Result;
-use crate::gatt::WriteRequest;
+use crate::gatt::{BluezError, ReadRequest, WriteRequest};
 use crate::gatt_application::{
     GattApplicationConfig, GattApplicationConfigBuilder, GattCharacteristicConfigBuilder,
     GattServiceConfigBuilder,
 };
 use lipl_display_common::{
-    CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_STATUS_UUID, CHARACTERISTIC_TEXT_UUID, LOCAL_NAME,
-    Message, SERVICE_UUID,
+    CHARACTERISTIC_COMMAND_UUID, CHARACTERISTIC_INFO_UUID, CHARACTERISTIC_PLAYLIST_UUID,
+    CHARACTERISTIC_SCREEN_UUID, CHARACTERISTIC_STATUS_UUID, CHARACTERISTIC_TEXT_UUID, Info,
+    LOCAL_NAME, LiplScreen, Message, SERVICE_UUID, Security,
+    access::{Access, device_address},
+    framing::Reassembler,
 };
-use std::convert::TryFrom;
 use std::{collections::HashMap, vec};
-use tracing::error;
+use tracing::{error, warn};
 use uuid::Uuid;
 
 pub fn gatt_application_config() -> Result<GattApplicationConfig> {
@@ -24,6 +26,24 @@ pub fn gatt_application_config() -> Result<GattApplicationConfig> {
 
     let char_command_config = GattCharacteristicConfigBuilder::default()
         .uuid(CHARACTERISTIC_COMMAND_UUID)
+        .security(Security::Authenticated)
+        .build()?;
+
+    let char_screen_config = GattCharacteristicConfigBuilder::default()
+        .uuid(CHARACTERISTIC_SCREEN_UUID)
+        .read(true)
+        .write(false)
+        .notify(true)
+        .build()?;
+
+    let char_info_config = GattCharacteristicConfigBuilder::default()
+        .uuid(CHARACTERISTIC_INFO_UUID)
+        .read(true)
+        .write(false)
+        .build()?;
+
+    let char_playlist_config = GattCharacteristicConfigBuilder::default()
+        .uuid(CHARACTERISTIC_PLAYLIST_UUID)
         .build()?;
 
     let service_config = GattServiceConfigBuilder::default()
@@ -32,6 +52,9 @@ pub fn gatt_application_config() -> Result<GattApplicationConfig> {
             char_text_config,
             char_status_config,
             char_command_config,
+            char_screen_config,
+            char_info_config,
+            char_playlist_config,
         ])
         .build()?;
 
@@ -43,32 +66,251 @@ pub fn gatt_application_config() -> Result<GattApplicationConfig> {
     Ok(app_config)
 }
 
+/// Long write waiting to be executed, on the characteristic with service uuid and uuid from device
+pub type LongWrite = ((Uuid, Uuid), Option<String>);
+
+/// Parts of a long write are reliable writes or have an offset, the last part is not marked
+pub fn is_long_write(write_request: &WriteRequest) -> bool {
+    write_request.write_type.as_deref() == Some("reliable")
+        || write_request.offset.unwrap_or_default() > 0
+}
+
+/// Message from the write request, once the value is complete and accepted
+///
+/// Parts of a long write are buffered until [`execute_long_write`].
 pub fn handle_write_request(
-    write_request: &mut WriteRequest,
-    map: &mut HashMap<(Uuid, Uuid), Vec<u8>>,
-) -> Option<Message> {
+    write_request: &WriteRequest,
+    map: &mut HashMap<(Uuid, Uuid), Reassembler>,
+    access: &Access,
+) -> Result<Option<Message>, BluezError> {
     let uuid = write_request.uuid;
-    let service_uuid = write_request.service_uuid;
-    match write_request.offset {
-        Some(offset) => {
-            error!("Cannot handle write request for {uuid} with offset {offset}");
-            None
-        }
-        None => match std::str::from_utf8(&write_request.value) {
-            Ok(s) => {
-                map.entry((service_uuid, uuid))
-                    .and_modify(|e| *e = write_request.value.clone());
-                Message::try_from((s, uuid)).ok()
-            }
-            Err(_) => None,
-        },
+    let address = write_request.device.as_deref().and_then(device_address);
+    let device = address.as_deref().unwrap_or("unknown device");
+    if !access.authorize(address.as_deref()) {
+        warn!("Write from {device} not authorized");
+        return Err(BluezError::NotAuthorized("Device not allowed".into()));
     }
+
+    let offset = write_request.offset.unwrap_or_default().into();
+    let reassembler = map.entry((write_request.service_uuid, uuid)).or_default();
+    let value = if is_long_write(write_request) {
+        reassembler
+            .prepare(offset, &write_request.value)
+            .map(|_| None)
+    } else {
+        reassembler.push(offset, &write_request.value)
+    }
+    .inspect_err(|error| error!("Cannot handle write request for {uuid}: {error}"))?;
+    accept(value, uuid, address.as_deref(), access)
 }
 
-pub fn characteristics_map() -> HashMap<(Uuid, Uuid), Vec<u8>> {
-    let mut map: HashMap<(Uuid, Uuid), Vec<u8>> = HashMap::new();
-    map.insert((SERVICE_UUID, CHARACTERISTIC_TEXT_UUID), vec![]);
-    map.insert((SERVICE_UUID, CHARACTERISTIC_STATUS_UUID), vec![]);
-    map.insert((SERVICE_UUID, CHARACTERISTIC_COMMAND_UUID), vec![]);
+/// Message from the long write that was prepared, if complete and accepted
+pub fn execute_long_write(
+    long_write: &LongWrite,
+    map: &mut HashMap<(Uuid, Uuid), Reassembler>,
+    access: &Access,
+) -> Result<Option<Message>, BluezError> {
+    let ((service_uuid, uuid), device) = long_write;
+    let address = device.as_deref().and_then(device_address);
+    let value = map
+        .get_mut(&(*service_uuid, *uuid))
+        .and_then(Reassembler::execute);
+    accept(value, *uuid, address.as_deref(), access)
+}
+
+fn accept(
+    value: Option<Vec<u8>>,
+    uuid: Uuid,
+    address: Option<&str>,
+    access: &Access,
+) -> Result<Option<Message>, BluezError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let device = address.unwrap_or("unknown device");
+    let value = std::str::from_utf8(&value).map_err(|error| {
+        warn!("Write to {uuid} from {device} rejected: {error}");
+        BluezError::Failed(format!("Value not utf-8: {error}"))
+    })?;
+    access
+        .accept(address, uuid, value)
+        .map(Some)
+        .map_err(|error| {
+            let count = access.count_rejection(&error);
+            warn!("Write to {uuid} from {device} rejected, {count} times so far: {error}");
+            BluezError::from(error)
+        })
+}
+
+pub fn handle_read_request(read_request: ReadRequest, screen: &LiplScreen, info: &Info) {
+    let uuid = read_request.uuid;
+    let json = if uuid == CHARACTERISTIC_SCREEN_UUID {
+        Some(screen.to_json())
+    } else if uuid == CHARACTERISTIC_INFO_UUID {
+        Some(info.to_json())
+    } else {
+        None
+    };
+    let value = match json {
+        Some(json) => json
+            .map(String::into_bytes)
+            .inspect_err(|error| error!("Cannot serialize value for {uuid}: {error}"))
+            .unwrap_or_default(),
+        None => vec![],
+    };
+    let offset = read_request.offset.unwrap_or_default().into();
+    if let Some(mut sender) = read_request.sender
+        && let Err(error) = sender.try_send(value.get(offset..).unwrap_or_default().to_vec())
+    {
+        error!("Cannot reply to read request for {uuid}: {error}");
+    }
+}
+
+pub fn characteristics_map() -> HashMap<(Uuid, Uuid), Reassembler> {
+    let mut map: HashMap<(Uuid, Uuid), Reassembler> = HashMap::new();
+    map.insert(
+        (SERVICE_UUID, CHARACTERISTIC_TEXT_UUID),
+        Reassembler::default(),
+    );
+    map.insert(
+        (SERVICE_UUID, CHARACTERISTIC_STATUS_UUID),
+        Reassembler::default(),
+    );
+    map.insert(
+        (SERVICE_UUID, CHARACTERISTIC_COMMAND_UUID),
+        Reassembler::default(),
+    );
+    map.insert(
+        (SERVICE_UUID, CHARACTERISTIC_PLAYLIST_UUID),
+        Reassembler::default(),
+    );
     map
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        characteristics_map, execute_long_write, gatt_application_config, handle_write_request,
+    };
+    use crate::gatt::{BluezError, WriteRequest};
+    use lipl_display_common::{
+        CHARACTERISTIC_STATUS_UUID, Message, SERVICE, SERVICE_UUID, Security,
+        access::{Access, AccessConfig},
+        validate::MAX_STATUS_LENGTH,
+    };
+
+    #[test]
+    fn service_characteristics() {
+        let config = gatt_application_config().unwrap();
+        assert_eq!(config.services.len(), 1);
+        assert_eq!(config.services[0].uuid, SERVICE.0);
+        let characteristics = &config.services[0].characteristics;
+        assert_eq!(
+            characteristics.iter().map(|c| c.uuid).collect::<Vec<_>>(),
+            SERVICE.1.to_vec()
+        );
+        assert!(characteristics[..3].iter().all(|c| c.write && !c.read));
+        assert!(characteristics[3].read && characteristics[3].notify && !characteristics[3].write);
+        assert!(characteristics[4].read && !characteristics[4].notify && !characteristics[4].write);
+        assert!(characteristics[5].write && !characteristics[5].read);
+        assert_eq!(
+            characteristics
+                .iter()
+                .map(|c| c.security)
+                .collect::<Vec<_>>(),
+            SERVICE
+                .1
+                .iter()
+                .map(|uuid| Security::default_for(*uuid))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn write_request(value: &str) -> WriteRequest {
+        WriteRequest {
+            uuid: CHARACTERISTIC_STATUS_UUID,
+            value: value.as_bytes().to_vec(),
+            mtu: None,
+            device: Some("/org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF".into()),
+            offset: None,
+            write_type: None,
+            service_uuid: SERVICE_UUID,
+            reply: None,
+        }
+    }
+
+    #[test]
+    fn rejected_writes() {
+        let mut map = characteristics_map();
+        let access = Access::new(AccessConfig {
+            burst: 2,
+            ..Default::default()
+        });
+        assert_eq!(
+            handle_write_request(&write_request("1/3\u{7}"), &mut map, &access).unwrap(),
+            Some(Message::Status("1/3".to_owned()))
+        );
+        let too_long = "x".repeat(MAX_STATUS_LENGTH + 1);
+        assert!(matches!(
+            handle_write_request(&write_request(&too_long), &mut map, &access),
+            Err(BluezError::InvalidValueLength(_))
+        ));
+        assert!(matches!(
+            handle_write_request(&write_request("2/3"), &mut map, &access),
+            Err(BluezError::NotPermitted(_))
+        ));
+    }
+
+    #[test]
+    fn long_write() {
+        let mut map = characteristics_map();
+        let access = Access::default();
+        let value = "Psalm 23, Café".as_bytes();
+        let long_write = (
+            (SERVICE_UUID, CHARACTERISTIC_STATUS_UUID),
+            write_request("").device,
+        );
+        // é is split over the last two parts of the long write
+        for (offset, part) in [(0, &value[..7]), (7, &value[7..14]), (14, &value[14..])] {
+            let request = WriteRequest {
+                value: part.to_vec(),
+                offset: Some(offset),
+                write_type: Some("reliable".into()),
+                ..write_request("")
+            };
+            assert_eq!(
+                handle_write_request(&request, &mut map, &access).unwrap(),
+                None
+            );
+        }
+        assert_eq!(
+            execute_long_write(&long_write, &mut map, &access).unwrap(),
+            Some(Message::Status("Psalm 23, Café".to_owned()))
+        );
+        assert_eq!(
+            execute_long_write(&long_write, &mut map, &access).unwrap(),
+            None
+        );
+
+        // A part that does not continue the long write
+        let request = WriteRequest {
+            offset: Some(3),
+            write_type: Some("reliable".into()),
+            ..write_request("x")
+        };
+        assert!(matches!(
+            handle_write_request(&request, &mut map, &access),
+            Err(BluezError::InvalidOffset(_))
+        ));
+
+        // Not utf-8
+        let request = WriteRequest {
+            value: vec![0xC3],
+            ..write_request("")
+        };
+        assert!(matches!(
+            handle_write_request(&request, &mut map, &access),
+            Err(BluezError::Failed(_))
+        ));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{Result, gatt::Characteristic};
+use std::collections::HashMap;
+use uuid::Uuid;
+use zbus::{Connection, zvariant::OwnedObjectPath};
+
+/// Changes the value of characteristics served by the gatt application
+///
+/// Clients that started notifications on the characteristic receive the new value.
+#[derive(Clone)]
+pub(crate) struct Notifier {
+    connection: Connection,
+    paths: HashMap<(Uuid, Uuid), OwnedObjectPath>,
+}
+
+impl Notifier {
+    pub fn new(connection: Connection, paths: HashMap<(Uuid, Uuid), OwnedObjectPath>) -> Self {
+        Self { connection, paths }
+    }
+
+    pub async fn notify(&self, service_uuid: Uuid, uuid: Uuid, value: Vec<u8>) -> Result<()> {
+        let Some(path) = self.paths.get(&(service_uuid, uuid)) else {
+            return Ok(());
+        };
+        let interface = self
+            .connection
+            .object_server()
+            .interface::<_, Characteristic>(path)
+            .await?;
+        let mut characteristic = interface.get_mut().await;
+        characteristic.value = value;
+        if characteristic.notifying {
+            characteristic
+                .value_changed(interface.signal_emitter())
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,14 @@
 use crate::{
     Result,
     advertisement::PeripheralAdvertisement,
+    agent::{AGENT_PATH, Agent, CAPABILITY},
     connection_extension::ConnectionExt,
     error::Error,
     gatt::{Application, Characteristic, Request, Service},
     gatt_application::{GattApplication, GattApplicationConfig},
+    notifier::Notifier,
     object_path_extensions::OwnedObjectPathExtensions,
-    proxy::{Adapter1Proxy, GattManager1Proxy, LEAdvertisingManager1Proxy},
+    proxy::{Adapter1Proxy, AgentManager1Proxy, GattManager1Proxy, LEAdvertisingManager1Proxy},
 };
 use futures::{
     FutureExt,
@@ -62,6 +64,27 @@ impl Peripheral {
             .map_err(Into::into)
     }
 
+    pub async fn agent_manager_proxy(&self) -> Result<AgentManager1Proxy<'_>> {
+        AgentManager1Proxy::new(&self.connection)
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Register agent as the default agent for pairing
+    pub async fn register_agent(&self, agent: Agent) -> Result<()> {
+        let agent_path = AGENT_PATH.to_owned_object_path();
+        self.object_server().at(&agent_path, agent).await?;
+        let agent_manager_proxy = self.agent_manager_proxy().await?;
+        agent_manager_proxy
+            .register_agent(&agent_path, CAPABILITY)
+            .await?;
+        agent_manager_proxy
+            .request_default_agent(&agent_path)
+            .await?;
+        tracing::info!("Agent {AGENT_PATH} registered with bluez");
+        Ok(())
+    }
+
     /// Creates a dbus connection to bluez
     /// Finds the first gatt capable adapter
     /// Set adapter powered and discoverable1
@@ -90,6 +113,7 @@ impl Peripheral {
         gatt_application_config: GattApplicationConfig,
     ) -> Result<(
         Receiver<Request>,
+        Notifier,
         Pin<Box<dyn Future<Output = Result<()>> + Send>>,
     )> {
         let (tx, rx) = channel::<Request>(1);
@@ -195,10 +219,24 @@ impl Peripheral {
             .inspect_err(|error| tracing::error!("Error: {}", error))?;
         tracing::info!("Application {app_op} registered with bluez");
 
+        let notifier = Notifier::new(
+            self.connection().clone(),
+            gatt_application
+                .characteristics
+                .iter()
+                .map(|characteristic| {
+                    (
+                        (characteristic.service_uuid, characteristic.uuid),
+                        characteristic.object_path.to_owned_object_path(),
+                    )
+                })
+                .collect(),
+        );
         let application = gatt_application;
 
         Ok((
             rx,
+            notifier,
             async move {
                 let gatt_manager_proxy = self.gatt_manager_proxy().await?;
                 let advertising_manager_proxy = self.advertising_manager_proxy().await?;
@@ -234,6 +272,20 @@ impl Peripheral {
                 .await;
                 remove_from_server::<Application>(self.object_server(), &app_object_path).await;
 
+                if self
+                    .object_server()
+                    .interface::<_, Agent>(AGENT_PATH)
+                    .await
+                    .is_ok()
+                {
+                    self.agent_manager_proxy()
+                        .await?
+                        .unregister_agent(&AGENT_PATH.to_owned_object_path())
+                        .await?;
+                    tracing::info!("Agent {AGENT_PATH} unregistered with bluez");
+                    remove_from_server::<Agent>(self.object_server(), AGENT_PATH).await;
+                }
+
                 Ok::<(), Error>(())
             }
             .boxed(),
@@ -0,0 +1,21 @@
+use zbus::proxy;
+
+#[proxy(
+    interface = "org.bluez.AgentManager1",
+    default_service = "org.bluez",
+    default_path = "/org/bluez"
+)]
+pub trait AgentManager1 {
+    /// RegisterAgent method
+    fn register_agent(
+        &self,
+        agent: &zbus::zvariant::ObjectPath<'_>,
+        capability: &str,
+    ) -> zbus::Result<()>;
+
+    /// RequestDefaultAgent method
+    fn request_default_agent(&self, agent: &zbus::zvariant::ObjectPath<'_>) -> zbus::Result<()>;
+
+    /// UnregisterAgent method
+    fn unregister_agent(&self, agent: &zbus::zvariant::ObjectPath<'_>) -> zbus::Result<()>;
+}
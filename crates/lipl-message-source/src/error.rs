@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+
+    #[cfg(feature = "bluer")]
+    #[error("Bluer error: {0}")]
+    Bluer(#[from] lipl_gatt_bluer::Error),
+
+    #[error("Invalid source: {0}")]
+    InvalidSource(String),
+
+    #[error("Source {0} not enabled, build with feature {0}")]
+    NotEnabled(String),
+
+    #[error("Osc error: {0}")]
+    Osc(String),
+
+    #[error("Access: {0}")]
+    Access(#[from] lipl_display_common::Error),
+}
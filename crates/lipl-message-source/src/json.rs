@@ -0,0 +1,106 @@
+use futures_util::{Stream, StreamExt, TryStreamExt, future::ready, stream::BoxStream};
+use lipl_display_common::{Message, MessageSource};
+use log::{error, warn};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::AsyncBufRead;
+
+/// Message source reading one json serialized message per line
+///
+/// The stream ends at the first line that cannot be read or parsed,
+/// unless created with [`JsonLinesSource::skip_invalid`].
+pub struct JsonLinesSource {
+    name: String,
+    stream: BoxStream<'static, Message>,
+}
+
+impl JsonLinesSource {
+    pub fn new<R>(name: impl Into<String>, reader: R) -> Self
+    where
+        R: AsyncBufRead + Send + 'static,
+    {
+        let name = name.into();
+        let source_name = name.clone();
+        Self {
+            name,
+            stream: json_lines::lines::<Message, _>(reader)
+                .into_stream()
+                .take_while(move |result| {
+                    ready(
+                        result
+                            .as_ref()
+                            .inspect_err(|e| error!("Error reading from {source_name}: {e}"))
+                            .is_ok(),
+                    )
+                })
+                .filter_map(|result| ready(result.ok()))
+                .boxed(),
+        }
+    }
+
+    /// Source logging and skipping lines that cannot be parsed
+    pub fn skip_invalid<R>(name: impl Into<String>, reader: R) -> Self
+    where
+        R: AsyncBufRead + Send + 'static,
+    {
+        let name = name.into();
+        let source_name = name.clone();
+        Self {
+            name,
+            stream: json_lines::lines_skip_invalid::<Message, _>(reader, move |error| {
+                warn!("Skipped invalid line from {source_name}: {error}")
+            })
+            .boxed(),
+        }
+    }
+}
+
+impl Stream for JsonLinesSource {
+    type Item = Message;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl MessageSource for JsonLinesSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::JsonLinesSource;
+    use futures_util::StreamExt;
+    use lipl_display_common::{Command, Message};
+
+    #[tokio::test]
+    async fn stops_at_invalid_line() {
+        let input = "{\"part\":\"Hallo\"}\n{\"command\":\"Dark\"}\ninvalid\n{\"status\":\"1/2\"}";
+        let messages = JsonLinesSource::new("test", input.as_bytes())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            messages,
+            vec![
+                Message::Part("Hallo".into()),
+                Message::Command(Command::Dark)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_invalid_line() {
+        let input = "{\"part\":\"Hallo\"}\ninvalid\n{\"status\":\"1/2\"}";
+        let messages = JsonLinesSource::skip_invalid("test", input.as_bytes())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            messages,
+            vec![
+                Message::Part("Hallo".into()),
+                Message::Status("1/2".to_owned())
+            ]
+        );
+    }
+}
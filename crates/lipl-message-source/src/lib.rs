@@ -0,0 +1,23 @@
+#![doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+
+mod error;
+mod json;
+mod listen;
+mod osc;
+mod process;
+mod record;
+mod replay;
+mod source;
+
+pub use error::Error;
+pub use json::JsonLinesSource;
+pub use listen::ListenSource;
+pub use osc::{
+    DEFAULT_OSC_PREFIX, OscAddresses, OscArgument, OscMessage, OscSource,
+    parse_packet as parse_osc_packet,
+};
+pub use process::{Backoff, ProcessSource};
+pub use record::{Recorded, RecordingSource};
+pub use replay::{ReplayControl, ReplayOptions, ReplaySource};
+pub use source::{Source, daemon_socket, open, open_recording};
+pub type Result<T> = std::result::Result<T, Error>;
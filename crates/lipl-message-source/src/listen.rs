@@ -0,0 +1,92 @@
+use crate::{Source, open, open_recording};
+use lipl_display_common::{BackgroundThread, Command, Message, MessageSource, Pager};
+
+use futures_util::StreamExt;
+use log::{error, info};
+use std::{path::PathBuf, thread::JoinHandle};
+
+/// Receives messages on a background thread and passes them to a callback
+///
+/// The first message passed is always a wait command.
+/// Playlists and their navigation commands are resolved into parts and statuses.
+pub struct ListenSource {
+    sender: Option<tokio::sync::oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ListenSource {
+    pub fn new(source: Source, callback: impl Fn(Message) + Send + 'static) -> Self {
+        Self::with_recording(source, None, callback)
+    }
+
+    /// Like [`ListenSource::new`], also recording the received messages if record is given
+    pub fn with_recording(
+        source: Source,
+        record: Option<PathBuf>,
+        callback: impl Fn(Message) + Send + 'static,
+    ) -> Self {
+        let (tx, mut rx) = tokio::sync::oneshot::channel::<()>();
+        let thread = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Unable to create tokio runtime");
+
+            runtime.block_on(async move {
+                let opened = match record {
+                    Some(path) => open_recording(&source, &path).await,
+                    None => open(&source).await,
+                };
+                let mut s = match opened {
+                    Ok(s) => s,
+                    Err(error) => {
+                        error!("Failed to open source {source}: {error}");
+                        return;
+                    }
+                };
+                info!("Receiving messages from {}", s.name());
+                callback(Message::Command(Command::Wait));
+                let mut pager = Pager::default();
+                loop {
+                    tokio::select! {
+                        option_message = s.next() => {
+                            match option_message {
+                                Some(message) => {
+                                    pager.resolve(message).into_iter().for_each(&callback);
+                                }
+                                None => break,
+                            }
+                        }
+                        received = &mut rx => {
+                            if let Err(error) = received {
+                                error!("Error receiving signal to quit background thread: {error}");
+                            }
+                            break;
+                        }
+                    }
+                }
+                s.stop().await;
+            });
+            info!("Background thread almost finished");
+        });
+        ListenSource {
+            sender: Some(tx),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl BackgroundThread for ListenSource {
+    fn stop(&mut self) {
+        if let Some(tx) = self.sender.take() {
+            if tx.send(()).is_err() {
+                info!("Background thread already finished");
+            }
+            if let Some(thread) = self.thread.take()
+                && thread.join().is_err()
+            {
+                error!("Error joining background thread");
+            }
+        }
+    }
+}
@@ -0,0 +1,406 @@
+use futures_util::{Stream, StreamExt, stream::BoxStream};
+use lipl_display_common::{Command, Message, MessageSource, access::Access};
+use log::{error, warn};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::net::UdpSocket;
+
+use crate::{Error, Result};
+
+/// Address prefix used when none is given
+pub const DEFAULT_OSC_PREFIX: &str = "/lipl";
+
+const BUNDLE: &str = "#bundle";
+const MAX_PACKET_SIZE: usize = 64 * 1024;
+
+/// Osc addresses that are mapped to messages, each taking a string argument
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OscAddresses {
+    /// Address for [`Message::Part`]
+    pub part: String,
+    /// Address for [`Message::Status`]
+    pub status: String,
+    /// Address for [`Message::Command`], with a code from `MESSAGES` or a json command
+    pub command: String,
+}
+
+impl OscAddresses {
+    /// Addresses `<prefix>/part`, `<prefix>/status` and `<prefix>/command`
+    pub fn with_prefix(prefix: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        Self {
+            part: format!("{prefix}/part"),
+            status: format!("{prefix}/status"),
+            command: format!("{prefix}/command"),
+        }
+    }
+
+    /// Message for an osc message, if its address is mapped
+    pub fn message(&self, osc: &OscMessage) -> Option<Result<Message>> {
+        let argument = || match osc.arguments.first() {
+            Some(OscArgument::String(s)) => Ok(s.clone()),
+            _ => Err(Error::Osc(format!(
+                "{} needs a string argument",
+                osc.address
+            ))),
+        };
+        if osc.address == self.part {
+            Some(argument().map(|s| Message::Part(s.into())))
+        } else if osc.address == self.status {
+            Some(argument().map(Message::Status))
+        } else if osc.address == self.command {
+            Some(argument().and_then(|s| {
+                s.parse::<Command>()
+                    .map(Message::Command)
+                    .map_err(|_| Error::Osc(format!("Invalid command {s}")))
+            }))
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for OscAddresses {
+    fn default() -> Self {
+        Self::with_prefix(DEFAULT_OSC_PREFIX)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArgument {
+    Int(i32),
+    Float(f32),
+    String(String),
+    Bool(bool),
+    Blob(Vec<u8>),
+}
+
+/// Message received in an osc packet
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub arguments: Vec<OscArgument>,
+}
+
+/// Reads the osc 1.0 encoding, with all values aligned at 4 bytes
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        if count > self.data.len() {
+            return Err(Error::Osc("Packet too short".to_owned()));
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn padded(&mut self, count: usize) -> Result<&'a [u8]> {
+        let taken = self.take(count)?;
+        self.take((4 - count % 4) % 4)?;
+        Ok(taken)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let end = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(Error::Osc("String not terminated".to_owned()))?;
+        let s = std::str::from_utf8(&self.data[..end])
+            .map_err(|error| Error::Osc(error.to_string()))?
+            .to_owned();
+        self.padded(end + 1)?;
+        Ok(s)
+    }
+
+    fn int(&mut self) -> Result<i32> {
+        let bytes = self.take(4)?;
+        Ok(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn length(&mut self) -> Result<usize> {
+        usize::try_from(self.int()?).map_err(|_| Error::Osc("Negative length".to_owned()))
+    }
+}
+
+/// Parse an osc packet, a message or a bundle of packets
+pub fn parse_packet(packet: &[u8]) -> Result<Vec<OscMessage>> {
+    let mut reader = Reader { data: packet };
+    let address = reader.string()?;
+    if address == BUNDLE {
+        // Time tag is ignored, bundle elements are handled on arrival
+        reader.take(8)?;
+        let mut messages = vec![];
+        while !reader.data.is_empty() {
+            let size = reader.length()?;
+            messages.extend(parse_packet(reader.take(size)?)?);
+        }
+        return Ok(messages);
+    }
+    if !address.starts_with('/') {
+        return Err(Error::Osc(format!("Invalid address {address}")));
+    }
+
+    // Messages without type tag string have no arguments
+    let tags = if reader.data.is_empty() {
+        String::new()
+    } else {
+        reader.string()?
+    };
+    let mut arguments = vec![];
+    for tag in tags.chars().skip_while(|c| *c == ',') {
+        match tag {
+            'i' => arguments.push(OscArgument::Int(reader.int()?)),
+            'f' => arguments.push(OscArgument::Float(f32::from_bits(reader.int()? as u32))),
+            's' | 'S' => arguments.push(OscArgument::String(reader.string()?)),
+            'b' => {
+                let size = reader.length()?;
+                arguments.push(OscArgument::Blob(reader.padded(size)?.to_vec()));
+            }
+            'T' => arguments.push(OscArgument::Bool(true)),
+            'F' => arguments.push(OscArgument::Bool(false)),
+            'N' | 'I' => {}
+            'h' | 't' | 'd' => {
+                reader.take(8)?;
+            }
+            'c' | 'r' | 'm' => {
+                reader.take(4)?;
+            }
+            _ => return Err(Error::Osc(format!("Unsupported type tag {tag}"))),
+        }
+    }
+    Ok(vec![OscMessage { address, arguments }])
+}
+
+/// Message for an osc message, checked with [`Access::accept_local`]
+fn accept(addresses: &OscAddresses, access: &Access, osc: &OscMessage) -> Option<Result<Message>> {
+    addresses
+        .message(osc)
+        .map(|message| Ok(access.accept_local(message?)?))
+}
+
+/// Message source receiving osc packets over udp
+///
+/// Packets that cannot be parsed and unmapped addresses are logged and skipped.
+/// Messages are checked with [`Access::accept_local`], so exit, poweroff
+/// and messages that have to be signed are refused.
+pub struct OscSource {
+    name: String,
+    stream: BoxStream<'static, Message>,
+}
+
+impl OscSource {
+    pub fn new(
+        name: impl Into<String>,
+        socket: UdpSocket,
+        addresses: OscAddresses,
+        access: Arc<Access>,
+    ) -> Self {
+        let stream = futures_util::stream::unfold(
+            (socket, addresses, access, vec![0; MAX_PACKET_SIZE]),
+            |(socket, addresses, access, mut buffer)| async move {
+                let messages = match socket.recv_from(&mut buffer).await {
+                    Ok((size, peer)) => match parse_packet(&buffer[..size]) {
+                        Ok(packet) => packet
+                            .iter()
+                            .filter_map(|osc| {
+                                let message = accept(&addresses, &access, osc);
+                                if message.is_none() {
+                                    warn!("No message for osc address {}", osc.address);
+                                }
+                                message
+                            })
+                            .filter_map(|message| {
+                                message
+                                    .inspect_err(|error| warn!("Osc from {peer}: {error}"))
+                                    .ok()
+                            })
+                            .collect::<Vec<_>>(),
+                        Err(error) => {
+                            warn!("Osc from {peer}: {error}");
+                            vec![]
+                        }
+                    },
+                    Err(error) => {
+                        error!("Error receiving osc: {error}");
+                        return None;
+                    }
+                };
+                Some((
+                    futures_util::stream::iter(messages),
+                    (socket, addresses, access, buffer),
+                ))
+            },
+        )
+        .flatten()
+        .boxed();
+        Self {
+            name: name.into(),
+            stream,
+        }
+    }
+}
+
+impl Stream for OscSource {
+    type Item = Message;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl MessageSource for OscSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OscAddresses, OscArgument, OscMessage, OscSource, accept, parse_packet};
+    use crate::Error;
+    use futures_util::StreamExt;
+    use lipl_display_common::{Command, Message, access::Access};
+    use std::sync::Arc;
+    use tokio::net::UdpSocket;
+
+    fn padded(s: &str) -> Vec<u8> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.push(0);
+        while !bytes.len().is_multiple_of(4) {
+            bytes.push(0);
+        }
+        bytes
+    }
+
+    fn message(address: &str, argument: &str) -> Vec<u8> {
+        [padded(address), padded(",s"), padded(argument)].concat()
+    }
+
+    fn bundle(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut bytes = [padded("#bundle"), vec![0, 0, 0, 0, 0, 0, 0, 1]].concat();
+        for packet in packets {
+            bytes.extend((packet.len() as i32).to_be_bytes());
+            bytes.extend(packet);
+        }
+        bytes
+    }
+
+    #[test]
+    fn parse() {
+        let packet = [padded("/cue/1"), padded(",isf"), vec![0, 0, 0, 42]]
+            .concat()
+            .into_iter()
+            .chain(padded("go"))
+            .chain(1.5f32.to_be_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parse_packet(&packet).unwrap(),
+            vec![OscMessage {
+                address: "/cue/1".to_owned(),
+                arguments: vec![
+                    OscArgument::Int(42),
+                    OscArgument::String("go".to_owned()),
+                    OscArgument::Float(1.5),
+                ],
+            }]
+        );
+        assert_eq!(
+            parse_packet(&bundle(&[message("/a", "1"), message("/b", "2")]))
+                .unwrap()
+                .len(),
+            2
+        );
+        assert!(parse_packet(&padded("/a")[..2]).is_err());
+        assert!(parse_packet(&[padded("/a"), padded(",s")].concat()).is_err());
+        assert!(parse_packet(&padded("lipl")).is_err());
+    }
+
+    #[test]
+    fn addresses() {
+        let addresses = OscAddresses::with_prefix("/show/");
+        assert_eq!(addresses.part, "/show/part");
+        let osc = |address: &str, argument: &str| OscMessage {
+            address: address.to_owned(),
+            arguments: vec![OscArgument::String(argument.to_owned())],
+        };
+        assert_eq!(
+            addresses
+                .message(&osc("/show/command", "d"))
+                .unwrap()
+                .unwrap(),
+            Message::Command(Command::Dark)
+        );
+        assert!(addresses.message(&osc("/lipl/part", "Hallo")).is_none());
+        assert!(
+            addresses
+                .message(&osc("/show/command", "x"))
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn refused() {
+        let addresses = OscAddresses::default();
+        let access = Access::default();
+        let osc = |argument: &str| OscMessage {
+            address: "/lipl/command".to_owned(),
+            arguments: vec![OscArgument::String(argument.to_owned())],
+        };
+        for command in ["o", "e"] {
+            assert!(matches!(
+                accept(&addresses, &access, &osc(command)),
+                Some(Err(Error::Access(lipl_display_common::Error::NotAllowed(
+                    _
+                ))))
+            ));
+        }
+        assert_eq!(
+            accept(&addresses, &access, &osc("d")).unwrap().unwrap(),
+            Message::Command(Command::Dark)
+        );
+    }
+
+    #[tokio::test]
+    async fn loopback() {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        let mut source = OscSource::new(
+            "osc",
+            socket,
+            OscAddresses::default(),
+            Arc::new(Access::default()),
+        );
+
+        let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        for packet in [
+            message("/lipl/part", "Amazing grace"),
+            message("/lipl/unknown", "skipped"),
+            message("/lipl/command", "o"),
+            b"invalid".to_vec(),
+            bundle(&[
+                message("/lipl/status", "1/3"),
+                message("/lipl/command", "+"),
+            ]),
+        ] {
+            sender.send_to(&packet, address).await.unwrap();
+        }
+
+        let mut messages = vec![];
+        for _ in 0..3 {
+            messages.push(source.next().await.unwrap());
+        }
+        assert_eq!(
+            messages,
+            vec![
+                Message::Part("Amazing grace".into()),
+                Message::Status("1/3".to_owned()),
+                Message::Command(Command::Increase),
+            ]
+        );
+    }
+}
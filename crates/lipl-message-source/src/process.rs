@@ -0,0 +1,207 @@
+use futures_util::{Stream, StreamExt, stream::BoxStream};
+use lipl_display_common::{Command, Message, MessageSource};
+use log::{error, info, warn};
+use std::pin::Pin;
+use std::process::Stdio;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::BufReader;
+use tokio::process::Child;
+
+/// Delays before restarting a child process that died, doubling from min up to max
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Backoff {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            min: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+        }
+    }
+}
+
+struct Running {
+    child: Child,
+    messages: BoxStream<'static, Message>,
+}
+
+struct State {
+    name: String,
+    program: String,
+    args: Vec<String>,
+    backoff: Backoff,
+    delay: Duration,
+    running: Option<Running>,
+    finished: bool,
+}
+
+impl State {
+    fn spawn(&self) -> std::io::Result<Running> {
+        let mut child = tokio::process::Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(std::io::Error::other("no stdout"))?;
+        let name = self.name.clone();
+        let messages =
+            json_lines::lines_skip_invalid::<Message, _>(BufReader::new(stdout), move |error| {
+                warn!("Skipped invalid line from {name}: {error}")
+            })
+            .boxed();
+        Ok(Running { child, messages })
+    }
+
+    async fn restart_later(&mut self) {
+        warn!("Restarting {} in {:?}", self.program, self.delay);
+        tokio::time::sleep(self.delay).await;
+        self.delay = (self.delay * 2).min(self.backoff.max);
+    }
+
+    async fn next(&mut self) -> Option<Message> {
+        loop {
+            if self.finished {
+                return None;
+            }
+            let Some(running) = self.running.as_mut() else {
+                match self.spawn() {
+                    Ok(running) => {
+                        info!("Started {}", self.program);
+                        self.running = Some(running);
+                    }
+                    Err(error) => {
+                        error!("Cannot start {}: {error}", self.program);
+                        self.restart_later().await;
+                    }
+                }
+                continue;
+            };
+            match running.messages.next().await {
+                Some(message) => {
+                    self.delay = self.backoff.min;
+                    // The child exits after these, as lipl-gatt-bluer-cli does
+                    self.finished =
+                        matches!(message, Message::Command(Command::Exit | Command::Poweroff));
+                    return Some(message);
+                }
+                None => {
+                    match running.child.wait().await {
+                        Ok(status) => warn!("{} exited with {status}", self.program),
+                        Err(error) => error!("Error waiting for {}: {error}", self.program),
+                    }
+                    self.running = None;
+                    self.restart_later().await;
+                }
+            }
+        }
+    }
+}
+
+/// Message source reading json lines from the output of a child process
+///
+/// The child is restarted with [`Backoff`] whenever it dies, until it sent an exit or poweroff command.
+/// Lines that cannot be parsed are logged and skipped, and the child is killed when the source is dropped.
+pub struct ProcessSource {
+    name: String,
+    stream: BoxStream<'static, Message>,
+}
+
+impl ProcessSource {
+    pub fn new(
+        name: impl Into<String>,
+        program: impl Into<String>,
+        args: Vec<String>,
+        backoff: Backoff,
+    ) -> Self {
+        let name = name.into();
+        let state = State {
+            name: name.clone(),
+            program: program.into(),
+            args,
+            backoff,
+            delay: backoff.min,
+            running: None,
+            finished: false,
+        };
+        Self {
+            name,
+            stream: futures_util::stream::unfold(state, |mut state| async move {
+                state.next().await.map(|message| (message, state))
+            })
+            .boxed(),
+        }
+    }
+}
+
+impl Stream for ProcessSource {
+    type Item = Message;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl MessageSource for ProcessSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Backoff, ProcessSource};
+    use futures_util::StreamExt;
+    use lipl_display_common::{Command, Message};
+    use std::time::Duration;
+
+    const BACKOFF: Backoff = Backoff {
+        min: Duration::from_millis(10),
+        max: Duration::from_millis(40),
+    };
+
+    fn shell(script: &str) -> ProcessSource {
+        ProcessSource::new(
+            "test",
+            "sh",
+            vec!["-c".to_owned(), script.to_owned()],
+            BACKOFF,
+        )
+    }
+
+    #[tokio::test]
+    async fn restarts() {
+        let messages = shell("echo starting; echo '{\"part\":\"Hallo\"}'")
+            .take(3)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(messages, vec![Message::Part("Hallo".into()); 3]);
+    }
+
+    #[tokio::test]
+    async fn exit() {
+        let messages = shell("echo '{\"status\":\"1/2\"}'; echo '{\"command\":\"Exit\"}'")
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            messages,
+            vec![
+                Message::Status("1/2".to_owned()),
+                Message::Command(Command::Exit)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_program() {
+        let mut source = ProcessSource::new("test", "lipl-does-not-exist", vec![], BACKOFF);
+        let next = tokio::time::timeout(Duration::from_millis(100), source.next()).await;
+        assert!(next.is_err());
+    }
+}
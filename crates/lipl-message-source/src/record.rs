@@ -0,0 +1,149 @@
+use futures_util::{FutureExt, Stream, StreamExt, future::BoxFuture};
+use lipl_display_common::{BoxMessageSource, Injector, Message, MessageSource};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::Result;
+
+/// Line in a recording, with the message received after elapsed milliseconds
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Recorded {
+    pub elapsed: u64,
+    pub message: Message,
+}
+
+/// Message source writing every message it passes on to a recording
+///
+/// A recording has one json serialized [`Recorded`] per line and can be replayed with `replay:<path>`.
+/// Lines are written by a task of their own, so a slow disk does not hold back the messages.
+pub struct RecordingSource {
+    name: String,
+    inner: BoxMessageSource,
+    sender: Option<mpsc::UnboundedSender<Recorded>>,
+    writer: Option<JoinHandle<()>>,
+    start: Instant,
+}
+
+impl RecordingSource {
+    /// Record messages from inner to a new file at path
+    ///
+    /// Needs to be called from within a tokio runtime.
+    pub fn create(inner: BoxMessageSource, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = tokio::fs::File::from_std(File::create(path)?);
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Recorded>();
+        let writer = tokio::spawn(async move {
+            while let Some(recorded) = receiver.recv().await {
+                if let Err(error) = write(&mut file, &recorded).await {
+                    error!("Error recording message: {error}");
+                }
+            }
+        });
+        Ok(Self {
+            name: format!("{} recorded to {}", inner.name(), path.display()),
+            inner,
+            sender: Some(sender),
+            writer: Some(writer),
+            start: Instant::now(),
+        })
+    }
+
+    fn record(&self, message: &Message) {
+        let recorded = Recorded {
+            elapsed: self.start.elapsed().as_millis() as u64,
+            message: message.clone(),
+        };
+        if let Some(sender) = &self.sender {
+            // Writer only stops after the sender is dropped
+            let _ = sender.send(recorded);
+        }
+    }
+}
+
+/// Write a line and flush it, so the recording survives a crash
+async fn write(file: &mut tokio::fs::File, recorded: &Recorded) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(recorded)?;
+    line.push(b'\n');
+    file.write_all(&line).await?;
+    file.flush().await
+}
+
+impl Stream for RecordingSource {
+    type Item = Message;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.inner.poll_next_unpin(cx);
+        if let Poll::Ready(Some(message)) = &poll {
+            self.record(message);
+        }
+        poll
+    }
+}
+
+impl MessageSource for RecordingSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn stop(&mut self) -> BoxFuture<'_, ()> {
+        async move {
+            self.inner.stop().await;
+            // Writer finishes the lines left once the sender is dropped
+            self.sender.take();
+            if let Some(writer) = self.writer.take()
+                && let Err(error) = writer.await
+            {
+                error!("Error finishing recording: {error}");
+            }
+        }
+        .boxed()
+    }
+
+    fn injector(&self) -> Option<Injector> {
+        self.inner.injector()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Recorded, RecordingSource};
+    use crate::JsonLinesSource;
+    use futures_util::StreamExt;
+    use lipl_display_common::{Command, Message, MessageSource};
+
+    #[tokio::test]
+    async fn record() {
+        let path = std::env::temp_dir().join(format!("lipl-record-{}.jsonl", std::process::id()));
+        let input = "{\"part\":\"Hallo\"}\n{\"command\":\"Dark\"}\n";
+        let mut source = RecordingSource::create(
+            Box::new(JsonLinesSource::new("test", input.as_bytes())),
+            &path,
+        )
+        .unwrap();
+        let messages = source.by_ref().collect::<Vec<_>>().await;
+        source.stop().await;
+        assert_eq!(messages.len(), 2);
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let recorded = recording
+            .lines()
+            .map(|line| serde_json::from_str::<Recorded>(line).unwrap())
+            .map(|recorded| recorded.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            recorded,
+            vec![
+                Message::Part("Hallo".into()),
+                Message::Command(Command::Dark)
+            ]
+        );
+    }
+}
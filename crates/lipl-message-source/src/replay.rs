@@ -0,0 +1,298 @@
+use futures_util::{Stream, StreamExt, TryStreamExt, stream::BoxStream};
+use lipl_display_common::{Message, MessageSource};
+use log::error;
+use std::fmt::Display;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::AsyncBufRead;
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::watch;
+
+use crate::{Error, Recorded, Result};
+
+/// How a recording is replayed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayOptions {
+    /// Factor by which the delays between messages are shortened
+    pub speed: f64,
+    /// Longest delay between two messages, after applying speed
+    pub max_delay: Option<Duration>,
+    /// Start paused, holding back the first message until resumed
+    pub paused: bool,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            max_delay: None,
+            paused: false,
+        }
+    }
+}
+
+impl ReplayOptions {
+    fn delay(&self, elapsed: u64) -> Duration {
+        // A very low speed waits forever instead of overflowing
+        let delay = Duration::try_from_secs_f64(elapsed as f64 / 1000.0 / self.speed)
+            .unwrap_or(Duration::MAX);
+        self.max_delay.map_or(delay, |max| delay.min(max))
+    }
+}
+
+impl FromStr for ReplayOptions {
+    type Err = Error;
+
+    /// Parse comma separated options, as `speed=2,max-delay=5,paused` with max-delay in seconds
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::InvalidSource(s.to_owned());
+        let mut options = Self::default();
+        for option in s.split(',').filter(|option| !option.is_empty()) {
+            if option == "paused" {
+                options.paused = true;
+                continue;
+            }
+            let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+            let value = value
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite() && *value > 0.0)
+                .ok_or_else(invalid)?;
+            match key {
+                "speed" => options.speed = value,
+                "max-delay" => {
+                    options.max_delay =
+                        Some(Duration::try_from_secs_f64(value).map_err(|_| invalid())?)
+                }
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl Display for ReplayOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut options = vec![];
+        if self.speed != 1.0 {
+            options.push(format!("speed={}", self.speed));
+        }
+        if let Some(max_delay) = self.max_delay {
+            options.push(format!("max-delay={}", max_delay.as_secs_f64()));
+        }
+        if self.paused {
+            options.push("paused".to_owned());
+        }
+        write!(f, "{}", options.join(","))
+    }
+}
+
+/// Pauses and resumes a replay, can be cloned and used from any thread
+#[derive(Clone)]
+pub struct ReplayControl {
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl ReplayControl {
+    /// Hold back the next message until resumed
+    pub fn pause(&self) {
+        self.paused.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.paused.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Pause a running replay, resume a paused one
+    pub fn toggle(&self) {
+        self.paused.send_modify(|paused| *paused = !*paused);
+    }
+
+    /// Toggle the replay on every SIGUSR1, until the replay is dropped
+    ///
+    /// Needs to be called from within a tokio runtime.
+    pub fn toggle_on_signal(&self) -> Result<()> {
+        let mut signal = signal(SignalKind::user_defined1())?;
+        let control = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(()) = signal.recv() => control.toggle(),
+                    _ = control.paused.closed() => break,
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Message source replaying a recording with the original delays between messages
+///
+/// The stream ends at the first line that cannot be read or parsed.
+pub struct ReplaySource {
+    name: String,
+    control: ReplayControl,
+    stream: BoxStream<'static, Message>,
+}
+
+impl ReplaySource {
+    pub fn new<R>(name: impl Into<String>, reader: R, options: ReplayOptions) -> Self
+    where
+        R: AsyncBufRead + Send + 'static,
+    {
+        let name = name.into();
+        let source_name = name.clone();
+        let (paused, receiver) = watch::channel(options.paused);
+        let recorded = json_lines::lines::<Recorded, _>(reader)
+            .into_stream()
+            .boxed();
+        let stream = futures_util::stream::unfold(
+            (recorded, 0, receiver),
+            move |(mut recorded, previous, mut paused)| {
+                let options = options.clone();
+                let source_name = source_name.clone();
+                async move {
+                    match recorded.next().await? {
+                        Ok(Recorded { elapsed, message }) => {
+                            tokio::time::sleep(options.delay(elapsed.saturating_sub(previous)))
+                                .await;
+                            // Sender lives in the control of this source
+                            let _ = paused.wait_for(|paused| !paused).await;
+                            Some((message, (recorded, elapsed, paused)))
+                        }
+                        Err(error) => {
+                            error!("Error reading from {source_name}: {error}");
+                            None
+                        }
+                    }
+                }
+            },
+        )
+        .boxed();
+        Self {
+            name,
+            control: ReplayControl {
+                paused: Arc::new(paused),
+            },
+            stream,
+        }
+    }
+
+    pub fn control(&self) -> ReplayControl {
+        self.control.clone()
+    }
+}
+
+impl Stream for ReplaySource {
+    type Item = Message;
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
+
+impl MessageSource for ReplaySource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ReplayOptions, ReplaySource};
+    use futures_util::StreamExt;
+    use lipl_display_common::{Command, Message};
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    const RECORDING: &str = "{\"elapsed\":0,\"message\":{\"part\":\"Hallo\"}}
+{\"elapsed\":1000,\"message\":{\"status\":\"1/2\"}}
+{\"elapsed\":61000,\"message\":{\"command\":\"Dark\"}}
+";
+
+    #[test]
+    fn options() {
+        for s in [
+            "",
+            "speed=2",
+            "max-delay=0.5",
+            "speed=0.5,max-delay=5",
+            "paused",
+        ] {
+            assert_eq!(s.parse::<ReplayOptions>().unwrap().to_string(), s);
+        }
+        for s in ["speed", "speed=0", "speed=-1", "pause=1", "max-delay=1e300"] {
+            assert!(s.parse::<ReplayOptions>().is_err());
+        }
+        let slow = "speed=1e-300".parse::<ReplayOptions>().unwrap();
+        assert_eq!(slow.delay(1000), Duration::MAX);
+        assert_eq!(slow.delay(0), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn original_timing() {
+        let start = Instant::now();
+        let source = ReplaySource::new("test", RECORDING.as_bytes(), ReplayOptions::default());
+        let received = source
+            .map(|message| (message, start.elapsed().as_secs()))
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            received,
+            vec![
+                (Message::Part("Hallo".into()), 0),
+                (Message::Status("1/2".to_owned()), 1),
+                (Message::Command(Command::Dark), 61),
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn speed_and_max_delay() {
+        let start = Instant::now();
+        let options = "speed=2,max-delay=10".parse::<ReplayOptions>().unwrap();
+        let source = ReplaySource::new("test", RECORDING.as_bytes(), options);
+        let elapsed = source
+            .map(|_| start.elapsed().as_millis())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(elapsed, vec![0, 500, 10_500]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn pause() {
+        let start = Instant::now();
+        let mut source = ReplaySource::new("test", RECORDING.as_bytes(), ReplayOptions::default());
+        let control = source.control();
+        assert!(source.next().await.is_some());
+
+        control.pause();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            control.resume();
+        });
+        assert_eq!(source.next().await, Some(Message::Status("1/2".to_owned())));
+        assert_eq!(start.elapsed().as_secs(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn start_paused() {
+        let start = Instant::now();
+        let options = "paused".parse::<ReplayOptions>().unwrap();
+        let mut source = ReplaySource::new("test", RECORDING.as_bytes(), options);
+        let control = source.control();
+        assert!(control.is_paused());
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            control.toggle();
+        });
+        assert_eq!(source.next().await, Some(Message::Part("Hallo".into())));
+        assert_eq!(start.elapsed().as_secs(), 3);
+    }
+}
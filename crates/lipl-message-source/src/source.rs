@@ -0,0 +1,265 @@
+use crate::{
+    Backoff, DEFAULT_OSC_PREFIX, Error, JsonLinesSource, OscAddresses, OscSource, ProcessSource,
+    RecordingSource, ReplayOptions, ReplaySource, Result,
+};
+use lipl_display_common::{BoxMessageSource, access::Access};
+use std::{
+    fmt::Display,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::{
+    io::BufReader,
+    net::{UdpSocket, UnixStream},
+};
+
+/// Unix socket on which lipl-displayd serves its clients
+///
+/// In `$XDG_RUNTIME_DIR` if set, otherwise in the temp dir.
+pub fn daemon_socket() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("lipl-displayd.sock")
+}
+
+/// Where a display receives its messages from
+///
+/// Defaults to the first gatt peripheral enabled with the features bluer and zbus, otherwise to stdin.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    /// Gatt peripheral using bluer
+    Bluer,
+    /// Gatt peripheral using zbus
+    Zbus,
+    /// Json lines on standard input
+    Stdin,
+    /// Json lines read from a file
+    File(PathBuf),
+    /// Json lines read from a file as it grows, skipping invalid lines
+    Follow(PathBuf),
+    /// Json lines read after connecting to a unix socket
+    Socket(PathBuf),
+    /// Json lines from lipl-displayd, on the socket of [`daemon_socket`]
+    Daemon,
+    /// Json lines on the output of a command, as lipl-gatt-bluer-cli, restarted when it dies
+    Process { program: String, args: Vec<String> },
+    /// Recording replayed with the delays between messages as recorded
+    Replay {
+        path: PathBuf,
+        options: ReplayOptions,
+    },
+    /// Osc packets received over udp on address, with addresses starting with prefix
+    Osc {
+        address: SocketAddr,
+        prefix: Option<String>,
+    },
+}
+
+impl Default for Source {
+    fn default() -> Self {
+        if cfg!(feature = "bluer") {
+            Source::Bluer
+        } else if cfg!(feature = "zbus") {
+            Source::Zbus
+        } else {
+            Source::Stdin
+        }
+    }
+}
+
+impl FromStr for Source {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            None => match s {
+                "bluer" => Ok(Source::Bluer),
+                "zbus" => Ok(Source::Zbus),
+                "stdin" => Ok(Source::Stdin),
+                "daemon" => Ok(Source::Daemon),
+                _ => Err(Error::InvalidSource(s.to_owned())),
+            },
+            Some(("file", path)) if !path.is_empty() => Ok(Source::File(path.into())),
+            Some(("follow", path)) if !path.is_empty() => Ok(Source::Follow(path.into())),
+            Some(("socket", path)) if !path.is_empty() => Ok(Source::Socket(path.into())),
+            Some(("process", command)) => {
+                let mut words = command.split_whitespace().map(str::to_owned);
+                match words.next() {
+                    Some(program) => Ok(Source::Process {
+                        program,
+                        args: words.collect(),
+                    }),
+                    None => Err(Error::InvalidSource(s.to_owned())),
+                }
+            }
+            Some(("replay", replay)) => {
+                let (path, options) = match replay.split_once(',') {
+                    Some((path, options)) => (path, options.parse()?),
+                    None => (replay, ReplayOptions::default()),
+                };
+                if path.is_empty() {
+                    return Err(Error::InvalidSource(s.to_owned()));
+                }
+                Ok(Source::Replay {
+                    path: path.into(),
+                    options,
+                })
+            }
+            Some(("osc", address)) => {
+                let (address, prefix) = match address.find('/') {
+                    Some(index) => (&address[..index], Some(address[index..].to_owned())),
+                    None => (address, None),
+                };
+                address
+                    .parse()
+                    .map(|address| Source::Osc { address, prefix })
+                    .map_err(|_| Error::InvalidSource(s.to_owned()))
+            }
+            Some(_) => Err(Error::InvalidSource(s.to_owned())),
+        }
+    }
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Bluer => write!(f, "bluer"),
+            Source::Zbus => write!(f, "zbus"),
+            Source::Stdin => write!(f, "stdin"),
+            Source::File(path) => write!(f, "file:{}", path.display()),
+            Source::Follow(path) => write!(f, "follow:{}", path.display()),
+            Source::Socket(path) => write!(f, "socket:{}", path.display()),
+            Source::Daemon => write!(f, "daemon"),
+            Source::Process { program, args } => {
+                write!(f, "process:{program}")?;
+                args.iter().try_for_each(|arg| write!(f, " {arg}"))
+            }
+            Source::Replay { path, options } if options == &ReplayOptions::default() => {
+                write!(f, "replay:{}", path.display())
+            }
+            Source::Replay { path, options } => write!(f, "replay:{},{options}", path.display()),
+            Source::Osc { address, prefix } => {
+                write!(f, "osc:{address}{}", prefix.as_deref().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Start receiving messages from source
+///
+/// Needs to be called from within a tokio runtime.
+pub async fn open(source: &Source) -> Result<BoxMessageSource> {
+    match source {
+        #[cfg(feature = "bluer")]
+        Source::Bluer => Ok(Box::new(lipl_gatt_bluer::listen_stream().await?)),
+        #[cfg(feature = "zbus")]
+        Source::Zbus => Ok(Box::new(lipl_gatt_zbus::GattListener::new())),
+        #[cfg(not(feature = "bluer"))]
+        Source::Bluer => Err(Error::NotEnabled(source.to_string())),
+        #[cfg(not(feature = "zbus"))]
+        Source::Zbus => Err(Error::NotEnabled(source.to_string())),
+        Source::Stdin => Ok(Box::new(JsonLinesSource::new(
+            source.to_string(),
+            BufReader::new(tokio::io::stdin()),
+        ))),
+        Source::File(path) => {
+            let reader = json_lines::file_reader(path.clone()).await?;
+            Ok(Box::new(JsonLinesSource::new(source.to_string(), reader)))
+        }
+        Source::Follow(path) => {
+            let reader =
+                json_lines::follow_reader(path.clone(), json_lines::FOLLOW_INTERVAL).await?;
+            Ok(Box::new(JsonLinesSource::skip_invalid(
+                source.to_string(),
+                reader,
+            )))
+        }
+        Source::Socket(path) => {
+            let stream = UnixStream::connect(path).await?;
+            Ok(Box::new(JsonLinesSource::new(
+                source.to_string(),
+                BufReader::new(stream),
+            )))
+        }
+        Source::Daemon => {
+            let stream = UnixStream::connect(daemon_socket()).await?;
+            Ok(Box::new(JsonLinesSource::new(
+                source.to_string(),
+                BufReader::new(stream),
+            )))
+        }
+        Source::Process { program, args } => Ok(Box::new(ProcessSource::new(
+            source.to_string(),
+            program,
+            args.clone(),
+            Backoff::default(),
+        ))),
+        Source::Replay { path, options } => {
+            let reader = json_lines::file_reader(path.clone()).await?;
+            let replay = ReplaySource::new(source.to_string(), reader, options.clone());
+            replay.control().toggle_on_signal()?;
+            Ok(Box::new(replay))
+        }
+        Source::Osc { address, prefix } => {
+            let socket = UdpSocket::bind(address).await?;
+            Ok(Box::new(OscSource::new(
+                source.to_string(),
+                socket,
+                OscAddresses::with_prefix(prefix.as_deref().unwrap_or(DEFAULT_OSC_PREFIX)),
+                Arc::new(Access::load()?),
+            )))
+        }
+    }
+}
+
+/// Start receiving messages from source, recording them to a new file at path
+///
+/// Needs to be called from within a tokio runtime.
+pub async fn open_recording(source: &Source, path: &Path) -> Result<BoxMessageSource> {
+    let source = open(source).await?;
+    Ok(Box::new(RecordingSource::create(source, path)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Source;
+
+    #[test]
+    fn parse() {
+        for s in [
+            "bluer",
+            "zbus",
+            "stdin",
+            "file:/tmp/input.txt",
+            "follow:/tmp/messages.jsonl",
+            "socket:/run/lipl.sock",
+            "daemon",
+            "process:lipl-gatt-bluer-cli",
+            "process:ssh pi lipl-gatt-bluer-cli",
+            "replay:/tmp/rehearsal.jsonl",
+            "replay:/tmp/rehearsal.jsonl,speed=2,max-delay=5",
+            "osc:0.0.0.0:9000",
+            "osc:127.0.0.1:53000/show/lyrics",
+            "osc:[::1]:9000/show",
+        ] {
+            assert_eq!(s.parse::<Source>().unwrap().to_string(), s);
+        }
+        for s in [
+            "",
+            "blue",
+            "file:",
+            "follow:",
+            "http:localhost",
+            "osc:9000",
+            "replay:",
+            "process:",
+            "process: ",
+            "replay:/tmp/a,fast",
+            "osc:localhost:9000",
+        ] {
+            assert!(s.parse::<Source>().is_err());
+        }
+    }
+}